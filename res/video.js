@@ -31,6 +31,8 @@ const captions_button = document.getElementById('captions');
 const picture_in_picture_button = document.getElementById('picture_in_picture');
 /** @type {HTMLButtonElement} */
 const download_button = document.getElementById('download');
+/** @type {HTMLButtonElement?} */
+const download_audio_button = document.getElementById('download_audio');
 /** @type {HTMLButtonElement} */
 const fullscreen_button = document.getElementById('fullscreen');
 
@@ -185,6 +187,19 @@ function handle_video_error(event)
 }
 
 
+/** Pause and show a popup if the browser can't decode the codecs of the video */
+function warn_unsupported_codecs()
+{
+	const type = video.dataset.type;
+	if (!type || video.canPlayType(type) !== '')
+		return;
+	video.autoplay = false;
+	video.pause();
+	dialog_message.innerText = `This browser might not be able to play the video (${type})`;
+	dialog.close();
+	dialog.showModal();
+}
+
 /**
  * Toggle hiding controls if touched, toggle play/pause if clicked
  * @param {PointerEvent} event
@@ -386,6 +401,17 @@ function start_download()
 }
 
 
+/** Start downloading the audio of the video */
+function start_audio_download()
+{
+	const a = document.createElement('a');
+	a.download = '';
+	a.href = download_audio_button.dataset.src;
+	a.click();
+	a.remove();
+}
+
+
 /** Restart the countdown that hides the controls */
 function start_hiding_controls()
 {
@@ -599,6 +625,8 @@ function main()
 	play_pause_button.onpointerup = handle_play_pointer_up;
 	captions_button.onclick = toggle_captions;
 	download_button.onclick = start_download;
+	if (download_audio_button)
+		download_audio_button.onclick = start_audio_download;
 	fullscreen_button.onclick = toggle_fullscreen;
 
 	controls.classList.remove('hidden');
@@ -608,6 +636,7 @@ function main()
 	else
 		picture_in_picture_button.classList.add('hidden');
 
+	warn_unsupported_codecs();
 	update_play_pause_button();
 	update_captions_button();
 	update_progress();
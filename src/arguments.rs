@@ -1,10 +1,31 @@
+use core::net::IpAddr;
+use core::net::Ipv4Addr;
+use core::net::Ipv6Addr;
+use core::net::SocketAddr;
 use core::str::FromStr;
+use core::time::Duration;
+
+use crate::json::*;
 
 
 /// Folder of videos, posters, and subtitles
 const DEFAULT_FOLDER: &str = ".";
 /// Port for the TCP server
 const DEFAULT_PORT: u16 = 80;
+/// Address of the network interface for the TCP server
+const DEFAULT_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+/// Seconds to wait for the next request before closing a connection
+const DEFAULT_IDLE_TIMEOUT: u64 = 15;
+/// Seconds to let the responses finish after being asked to stop
+const DEFAULT_SHUTDOWN_TIMEOUT: u64 = 30;
+/// Maximum amount of open connections, where 0 is unlimited
+const DEFAULT_MAX_CONNECTIONS: usize = 0;
+/// Most threads which can make the responses, so a typo doesn't try to start millions of them
+const MAX_THREADS: usize = 1024;
+
+/// Keys in the config file
+const SETTINGS: [&str; 10] = ["folder", "folders", "listen", "port", "address", "threads", "idle_timeout", "shutdown_timeout",
+	"max_connections", "log"];
 
 
 /// Program mode from CLI arguments
@@ -21,60 +42,211 @@ use Mode::*;
 /// Configuration for this application from JSON
 pub struct Config
 {
-	/// Root folder of the videos, which is "." by default
-	pub folder: String,
-	/// Port to listen to, which is 80 by default
+	/// Root folders of the videos, which is "." by default, where the first folder
+	/// with a file is used and the folder listings are combined
+	pub folders: Vec<String>,
+	/// Addresses and ports to listen to, which is the address and port by default
+	pub listen: Vec<SocketAddr>,
+	/// Port to listen to if there are no listen addresses, which is 80 by default
 	pub port: u16,
+	/// Address of the network interface to listen to if there are no listen addresses,
+	/// which is all of them by default
+	pub address: IpAddr,
+	/// Amount of threads to make the responses and send the files up to 1024, which is the amount of processors if it's 0
+	pub threads: usize,
+	/// Time to wait for the next request or for the client to read more of a response before closing a connection,
+	/// which is 15 seconds by default
+	pub idle_timeout: Duration,
+	/// Time to let the responses finish after being asked to stop, which is 30 seconds by default
+	pub shutdown_timeout: Duration,
+	/// Maximum amount of open connections, where more are told to try again later, which is unlimited if it's 0
+	pub max_connections: usize,
+	/// Messages to print while running, which is errors by default
+	pub log: Log,
+}
+
+
+/// Amount of messages to print while running
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub enum Log
+{
+	Nothing,
+	Errors,
+	Requests,
+}
+
+
+/// Settings from the command line, which override the config file
+#[derive(Default)]
+struct Overrides
+{
+	folders: Vec<String>,
+	listen: Vec<SocketAddr>,
+	port: Option<u16>,
+	address: Option<IpAddr>,
+	threads: Option<usize>,
+	idle_timeout: Option<Duration>,
+	shutdown_timeout: Option<Duration>,
+	max_connections: Option<usize>,
+	log: Option<Log>,
 }
 
 
 impl Mode
 {
-	/// Construct from the arguments
+	/// Construct from the arguments of the program
 	pub fn new() -> Mode
+	{
+		return Mode::parse(std::env::args().skip(1));
+	}
+
+
+	/// Construct from the arguments after the name of the program
+	fn parse(arguments: impl Iterator<Item = String>) -> Mode
 	{
 		#[derive(Clone, Copy)]
 		enum State
 		{
 			Begin,
+			ConfigFile,
 			Folder,
+			Listen,
 			Port,
+			Address,
+			Threads,
+			IdleTimeout,
+			ShutdownTimeout,
+			MaxConnections,
+			Logging,
 		}
 		use State::*;
 
+		const CONFIG: &str = "--config";
 		const FOLDER: &str = "--folder";
+		const LISTEN: &str = "--listen";
 		const PORT: &str = "--port";
+		const ADDRESS: &str = "--address";
+		const THREADS: &str = "--threads";
+		const IDLE_TIMEOUT: &str = "--idle-timeout";
+		const SHUTDOWN_TIMEOUT: &str = "--shutdown-timeout";
+		const MAX_CONNECTIONS: &str = "--max-connections";
+		const LOG: &str = "--log";
 
 		let mut state = Begin;
 
-		let mut folder = String::new();
-		let mut port = DEFAULT_PORT;
+		let mut config_path = String::new();
+		let mut overrides = Overrides::default();
 
 		let mut arg_copy = "";
 
-		for arg in std::env::args().skip(1) {
+		for arg in arguments {
 			match (state, arg.as_str()) {
 				(_, "-h" | "--help") => return Help,
 				(_, "-v" | "--version") => return Version,
+				(Begin, CONFIG) => {
+					state = ConfigFile;
+					arg_copy = CONFIG;
+				},
 				(Begin, FOLDER) => {
 					state = Folder;
 					arg_copy = FOLDER;
 				},
+				(Begin, LISTEN) => {
+					state = Listen;
+					arg_copy = LISTEN;
+				},
 				(Begin, PORT) => {
 					state = Port;
 					arg_copy = PORT;
 				},
+				(Begin, ADDRESS) => {
+					state = Address;
+					arg_copy = ADDRESS;
+				},
+				(Begin, THREADS) => {
+					state = Threads;
+					arg_copy = THREADS;
+				},
+				(Begin, IDLE_TIMEOUT) => {
+					state = IdleTimeout;
+					arg_copy = IDLE_TIMEOUT;
+				},
+				(Begin, SHUTDOWN_TIMEOUT) => {
+					state = ShutdownTimeout;
+					arg_copy = SHUTDOWN_TIMEOUT;
+				},
+				(Begin, MAX_CONNECTIONS) => {
+					state = MaxConnections;
+					arg_copy = MAX_CONNECTIONS;
+				},
+				(Begin, LOG) => {
+					state = Logging;
+					arg_copy = LOG;
+				},
+				(ConfigFile, _) => {
+					config_path = arg;
+					state = Begin;
+				},
 				(Folder, _) => {
-					folder = arg;
+					overrides.folders.push(arg);
+					state = Begin;
+				},
+				(Listen, _) => {
+					match parse_listen_address(&arg) {
+						Some(address) => overrides.listen.push(address),
+						None => return Error(format!("Expected an address and port like \"0.0.0.0:80\", \"[::1]:80\", or \"*:80\" but got \"{arg}\"\n")),
+					}
 					state = Begin;
 				},
 				(Port, _) => {
-					port = match u16::from_str(&arg) {
-						Ok(port) => port,
+					overrides.port = match u16::from_str(&arg) {
+						Ok(port) => Some(port),
 						Err(_) => return Error(format!("Expected a port number but got \"{arg}\"\n")),
 					};
 					state = Begin;
 				},
+				(Address, _) => {
+					overrides.address = match IpAddr::from_str(&arg) {
+						Ok(address) => Some(address),
+						Err(_) => return Error(format!("Expected an IP address but got \"{arg}\"\n")),
+					};
+					state = Begin;
+				},
+				(Threads, _) => {
+					overrides.threads = match usize::from_str(&arg) {
+						Ok(threads) if threads <= MAX_THREADS => Some(threads),
+						_ => return Error(format!("Expected an amount of threads up to {MAX_THREADS} but got \"{arg}\"\n")),
+					};
+					state = Begin;
+				},
+				(IdleTimeout, _) => {
+					overrides.idle_timeout = match u64::from_str(&arg) {
+						Ok(seconds) if seconds > 0 => Some(Duration::from_secs(seconds)),
+						_ => return Error(format!("Expected a number of seconds which is more than 0 but got \"{arg}\"\n")),
+					};
+					state = Begin;
+				},
+				(ShutdownTimeout, _) => {
+					overrides.shutdown_timeout = match u64::from_str(&arg) {
+						Ok(seconds) => Some(Duration::from_secs(seconds)),
+						Err(_) => return Error(format!("Expected a number of seconds but got \"{arg}\"\n")),
+					};
+					state = Begin;
+				},
+				(MaxConnections, _) => {
+					overrides.max_connections = match usize::from_str(&arg) {
+						Ok(max_connections) => Some(max_connections),
+						Err(_) => return Error(format!("Expected an amount of connections but got \"{arg}\"\n")),
+					};
+					state = Begin;
+				},
+				(Logging, _) => {
+					overrides.log = match Log::from_name(&arg) {
+						Some(log) => Some(log),
+						None => return Error(format!("Expected \"none\", \"errors\", or \"requests\" but got \"{arg}\"\n")),
+					};
+					state = Begin;
+				},
 				_ => return Error(format!("Expected a valid argument but got \"{arg}\"\n")),
 			}
 		}
@@ -83,13 +255,273 @@ impl Mode
 			return Error(format!("Expected an argument after \"{arg_copy}\"\n"))
 		}
 
-		if folder.is_empty() {
-			folder = String::from(DEFAULT_FOLDER);
+		// Start with the defaults, then the config file, then the arguments
+		let mut config = Config {
+			folders: Vec::new(),
+			listen: Vec::new(),
+			port: DEFAULT_PORT,
+			address: DEFAULT_ADDRESS,
+			threads: 0,
+			idle_timeout: Duration::from_secs(DEFAULT_IDLE_TIMEOUT),
+			shutdown_timeout: Duration::from_secs(DEFAULT_SHUTDOWN_TIMEOUT),
+			max_connections: DEFAULT_MAX_CONNECTIONS,
+			log: Log::Errors,
+		};
+
+		if !config_path.is_empty() && let Err(error) = config.load(&config_path) {
+			return Error(error);
+		}
+
+		config.apply(overrides);
+
+		if config.folders.is_empty() {
+			config.folders.push(String::from(DEFAULT_FOLDER));
+		}
+		if config.listen.is_empty() {
+			config.listen.push(SocketAddr::new(config.address, config.port));
+		}
+
+		return Normal(config);
+	}
+}
+
+
+impl Config
+{
+	/// Change the settings which are in the JSON file
+	fn load(&mut self, path: &str) -> Result<(), String>
+	{
+		let text = match std::fs::read_to_string(path) {
+			Ok(text) => text,
+			Err(error) => return Err(format!("Failed to read the config file \"{path}\" - {error}\n")),
+		};
+
+		let json = match Json::parse(&text) {
+			Ok(json) => json,
+			Err(error) => return Err(format!("Error in \"{path}\" on line {}: {}\n", error.line, error.message)),
+		};
+
+		let entries = match &json.value {
+			JsonValue::Object(entries) => entries,
+			_ => return Err(format!("Error in \"{path}\" on line {}: Expected an object but got {}\n", json.line, json.kind_name())),
+		};
+
+		for (key, value) in entries {
+			if !SETTINGS.contains(&key.as_str()) {
+				return Err(format!("Error in \"{path}\" on line {}: Unknown setting \"{key}\"\n", value.line));
+			}
+			if let Err(expected) = self.load_setting(key, value) {
+				return Err(format!("Error in \"{path}\" on line {}: \"{key}\" expected {expected} but got {}\n",
+					value.line, describe(value)));
+			}
+		}
+
+		return Ok(());
+	}
+
+
+	/// Change one setting from the JSON file, otherwise describe what was expected
+	fn load_setting(&mut self, key: &str, value: &Json) -> Result<(), &'static str>
+	{
+		match key {
+			"folder" | "folders" => {
+				const EXPECTED: &str = "a folder or an array of folders";
+				self.folders = match &value.value {
+					JsonValue::String(folder) => vec![folder.clone()],
+					JsonValue::Array(folders) => {
+						let folders: Option<Vec<String>> = folders.iter()
+							.map(|folder| folder.as_str().map(String::from))
+							.collect();
+						match folders {
+							Some(folders) if !folders.is_empty() => folders,
+							_ => return Err(EXPECTED),
+						}
+					},
+					_ => return Err(EXPECTED),
+				};
+			},
+			"listen" => {
+				const EXPECTED: &str = "an address and port like \"0.0.0.0:80\" or an array of them";
+				let addresses = match &value.value {
+					JsonValue::String(address) => vec![parse_listen_address(address)],
+					JsonValue::Array(addresses) => addresses.iter()
+						.map(|address| address.as_str().and_then(parse_listen_address))
+						.collect(),
+					_ => return Err(EXPECTED),
+				};
+				self.listen = match addresses.into_iter().collect::<Option<Vec<SocketAddr>>>() {
+					Some(addresses) if !addresses.is_empty() => addresses,
+					_ => return Err(EXPECTED),
+				};
+			},
+			"port" => {
+				self.port = match value.as_f64() {
+					Some(port) if port.fract() == 0.0 && (0.0..=65535.0).contains(&port) => port as u16,
+					_ => return Err("an integer from 0 to 65535"),
+				};
+			},
+			"address" => {
+				self.address = match value.as_str().map(IpAddr::from_str) {
+					Some(Ok(address)) => address,
+					_ => return Err("an IP address like \"0.0.0.0\" or \"::\""),
+				};
+			},
+			"threads" => {
+				self.threads = match value.as_f64() {
+					Some(threads) if threads.fract() == 0.0 && (0.0..=MAX_THREADS as f64).contains(&threads) => threads as usize,
+					_ => return Err("an integer from 0 to 1024"),
+				};
+			},
+			"idle_timeout" => {
+				self.idle_timeout = match value.as_f64().filter(|&seconds| seconds > 0.0).map(Duration::try_from_secs_f64) {
+					Some(Ok(timeout)) => timeout,
+					_ => return Err("a number of seconds which is more than 0"),
+				};
+			},
+			"shutdown_timeout" => {
+				self.shutdown_timeout = match value.as_f64().map(Duration::try_from_secs_f64) {
+					Some(Ok(timeout)) => timeout,
+					_ => return Err("a number of seconds which is at least 0"),
+				};
+			},
+			"max_connections" => {
+				self.max_connections = match value.as_f64() {
+					Some(max_connections) if max_connections.fract() == 0.0 && max_connections >= 0.0 => max_connections as usize,
+					_ => return Err("an integer which is at least 0"),
+				};
+			},
+			"log" => {
+				self.log = match value.as_str().map(Log::from_name) {
+					Some(Some(log)) => log,
+					_ => return Err("\"none\", \"errors\", or \"requests\""),
+				};
+			},
+			_ => (),
+		}
+
+		return Ok(());
+	}
+
+
+	/// Change the settings which were given as arguments
+	fn apply(&mut self, overrides: Overrides)
+	{
+		if !overrides.folders.is_empty() {
+			self.folders = overrides.folders;
+		}
+		// A port or address from the arguments replaces the listen addresses from the config file
+		if !overrides.listen.is_empty() {
+			self.listen = overrides.listen;
+		}
+		else if overrides.port.is_some() || overrides.address.is_some() {
+			self.listen.clear();
+		}
+		if let Some(port) = overrides.port {
+			self.port = port;
+		}
+		if let Some(address) = overrides.address {
+			self.address = address;
+		}
+		if let Some(threads) = overrides.threads {
+			self.threads = threads;
+		}
+		if let Some(idle_timeout) = overrides.idle_timeout {
+			self.idle_timeout = idle_timeout;
+		}
+		if let Some(shutdown_timeout) = overrides.shutdown_timeout {
+			self.shutdown_timeout = shutdown_timeout;
+		}
+		if let Some(max_connections) = overrides.max_connections {
+			self.max_connections = max_connections;
+		}
+		if let Some(log) = overrides.log {
+			self.log = log;
+		}
+	}
+}
+
+
+impl Log
+{
+	/// Get the setting from a name like "errors"
+	fn from_name(name: &str) -> Option<Log>
+	{
+		return match name {
+			"none" => Some(Log::Nothing),
+			"errors" => Some(Log::Errors),
+			"requests" => Some(Log::Requests),
+			_ => None,
+		};
+	}
+}
+
+
+/// Parse an address and port like "0.0.0.0:80" or "[::1]:80", where "*:80" is every IPv4 and IPv6 address
+fn parse_listen_address(text: &str) -> Option<SocketAddr>
+{
+	return match text.strip_prefix("*:") {
+		Some(port) => u16::from_str(port).ok().map(|port| SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), port)),
+		None => SocketAddr::from_str(text).ok(),
+	};
+}
+
+
+/// Describe a JSON value for an error message, like `"abc"` or `an array`
+fn describe(value: &Json) -> String
+{
+	return match &value.value {
+		JsonValue::String(string) => format!("\"{string}\""),
+		JsonValue::Number(number) => number.to_string(),
+		JsonValue::Bool(boolean) => boolean.to_string(),
+		_ => value.kind_name().to_string(),
+	};
+}
+
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+
+	/// Parse the arguments after the name of the program
+	fn parse(arguments: &[&str]) -> Mode
+	{
+		return Mode::parse(arguments.iter().map(|argument| String::from(*argument)));
+	}
+
+
+	#[test]
+	fn idle_timeout()
+	{
+		match parse(&["--idle-timeout", "5"]) {
+			Normal(config) => assert_eq!(config.idle_timeout, Duration::from_secs(5)),
+			_ => panic!("expected a config"),
+		}
+		for seconds in ["0", "-1", "1.5", ""] {
+			match parse(&["--idle-timeout", seconds]) {
+				Error(error) => assert!(error.contains("more than 0"), "{error}"),
+				_ => panic!("expected an error for \"{seconds}\""),
+			}
 		}
+	}
+
+
+	#[test]
+	fn arguments_override_listen()
+	{
+		let path = std::env::temp_dir().join(format!("seeslug-{}-listen.json", std::process::id()));
+		std::fs::write(&path, r#"{"listen": ["127.0.0.1:8080"], "port": 8081}"#).unwrap();
+		let path = path.to_str().unwrap();
+		let listen = |arguments: &[&str]| match parse(&[&["--config", path], arguments].concat()) {
+			Normal(config) => config.listen,
+			_ => panic!("expected a config"),
+		};
 
-		return Normal(Config {
-			folder,
-			port,
-		});
+		assert_eq!(listen(&[]), [SocketAddr::from(([127, 0, 0, 1], 8080))]);
+		assert_eq!(listen(&["--port", "9000"]), [SocketAddr::from(([0, 0, 0, 0], 9000))]);
+		assert_eq!(listen(&["--address", "::1"]), [SocketAddr::from((Ipv6Addr::LOCALHOST, 8081))]);
+		assert_eq!(listen(&["--listen", "[::1]:9001", "--port", "9000"]), [SocketAddr::from((Ipv6Addr::LOCALHOST, 9001))]);
+		std::fs::remove_file(path).unwrap();
 	}
 }
@@ -0,0 +1,321 @@
+use std::collections::BTreeMap;
+
+use crate::webvtt::*;
+
+
+/// Size of the video which the positions are relative to if the script doesn't have it
+const DEFAULT_PLAY_SIZE: (f64, f64) = (384.0, 288.0);
+/// Fields of a style if the script doesn't have a format line
+const DEFAULT_STYLE_FORMAT: &str = "Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, \
+	Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding";
+/// Fields of an event if the script doesn't have a format line
+const DEFAULT_EVENT_FORMAT: &str = "Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text";
+
+
+/// How the text of a style is placed
+struct Style
+{
+	/// Position like a number pad, where 1 is the bottom left and 9 is the top right
+	alignment: u8,
+	/// Distance from the left, right, and bottom or top of the video for the alignment
+	margins: (f64, f64, f64),
+	bold: bool,
+	italic: bool,
+}
+
+
+/// Where the text of an event is placed, which the override tags can change
+struct Placement
+{
+	/// Position like a number pad, where 1 is the bottom left and 9 is the top right
+	alignment: u8,
+	/// Point like `\pos(100,200)` which the alignment is relative to, instead of the margins
+	position: Option<(f64, f64)>,
+}
+
+
+impl Placement
+{
+	/// Get the WebVTT cue settings like "line:5% align:start", where the text is at the bottom center by default
+	fn to_settings(&self, margins: (f64, f64, f64), play_size: (f64, f64)) -> String
+	{
+		let (left, right, vertical) = margins;
+		let (width, height) = play_size;
+		let column = (self.alignment - 1) % 3;
+		let row = (self.alignment - 1) / 3;
+
+		let mut result = Vec::new();
+		match self.position {
+			Some((x, y)) => {
+				let line_align = ["end", "center", "start"][row as usize];
+				let position_align = ["line-left", "center", "line-right"][column as usize];
+				result.push(format!("line:{}%,{line_align}", format_percent(y / height)));
+				result.push(format!("position:{}%,{position_align}", format_percent(x / width)));
+			},
+			None => {
+				match row {
+					0 if vertical > 0.0 => result.push(format!("line:{}%,end", format_percent(1.0 - vertical / height))),
+					0 => (),
+					1 => result.push(String::from("line:50%,center")),
+					_ => result.push(format!("line:{}%,start", format_percent(vertical / height))),
+				}
+				match column {
+					0 => result.push(format!("position:{}%,line-left", format_percent(left / width))),
+					2 => result.push(format!("position:{}%,line-right", format_percent(1.0 - right / width))),
+					_ => (),
+				}
+			},
+		}
+		match column {
+			0 => result.push(String::from("align:start")),
+			2 => result.push(String::from("align:end")),
+			_ => (),
+		}
+		return result.join(" ");
+	}
+}
+
+
+/// Convert SubStation Alpha subtitles (`.ssa` or `.ass`) to WebVTT, where the alignment, margins, and positions are
+/// cue settings, italic and bold are tags, and the other overrides like fonts and colors are removed
+/// https://github.com/libass/libass/wiki/ASS-File-Format-Guide
+pub fn ass_to_webvtt(text: &str) -> String
+{
+	let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+
+	let mut play_size = DEFAULT_PLAY_SIZE;
+	let mut styles = BTreeMap::new();
+	let mut style_format = parse_format(DEFAULT_STYLE_FORMAT);
+	let mut event_format = parse_format(DEFAULT_EVENT_FORMAT);
+	let mut section = String::new();
+	let mut cues = Vec::new();
+
+	for line in text.lines() {
+		let line = line.trim();
+		if line.starts_with('[') && line.ends_with(']') {
+			section = line.to_ascii_lowercase();
+			continue;
+		}
+		let (key, value) = match line.split_once(':') {
+			Some((key, value)) => (key.trim(), value.trim()),
+			None => continue,
+		};
+
+		match (section.as_str(), key) {
+			("[script info]", "PlayResX") => play_size.0 = value.parse().unwrap_or(play_size.0),
+			("[script info]", "PlayResY") => play_size.1 = value.parse().unwrap_or(play_size.1),
+			("[v4+ styles]" | "[v4 styles]", "Format") => style_format = parse_format(value),
+			("[v4+ styles]" | "[v4 styles]", "Style") => {
+				let fields = split_fields(value, style_format.len());
+				let field = |name: &str| get_field(&style_format, &fields, name);
+				let alignment = field("alignment").parse().unwrap_or(2);
+				let style = Style {
+					alignment: match section == "[v4 styles]" {
+						true => ssa_alignment(alignment),
+						false => alignment.clamp(1, 9),
+					},
+					margins: (
+						field("marginl").parse().unwrap_or_default(),
+						field("marginr").parse().unwrap_or_default(),
+						field("marginv").parse().unwrap_or_default(),
+					),
+					bold: !matches!(field("bold"), "" | "0"),
+					italic: !matches!(field("italic"), "" | "0"),
+				};
+				styles.insert(field("name").trim_start_matches('*').to_string(), style);
+			},
+			("[events]", "Format") => event_format = parse_format(value),
+			("[events]", "Dialogue") => {
+				let fields = split_fields(value, event_format.len());
+				let field = |name: &str| get_field(&event_format, &fields, name);
+				let (start, end) = match (parse_timestamp(field("start")), parse_timestamp(field("end"))) {
+					(Some(start), Some(end)) => (start, end),
+					_ => continue,
+				};
+				let style = styles.get(field("style").trim_start_matches('*'));
+
+				// Margins of the event replace the ones of the style unless they're 0
+				let style_margins = style.map_or((0.0, 0.0, 0.0), |style| style.margins);
+				let margin = |name: &str, default: f64| match field(name).parse::<f64>() {
+					Ok(margin) if margin != 0.0 => margin,
+					_ => default,
+				};
+				let margins = (margin("marginl", style_margins.0), margin("marginr", style_margins.1), margin("marginv", style_margins.2));
+
+				let mut placement = Placement {
+					alignment: style.map_or(2, |style| style.alignment),
+					position: None,
+				};
+				let mut text = convert_text(field("text"), &mut placement);
+				if text.is_empty() {
+					continue;
+				}
+				if let Some(style) = style {
+					if style.italic {
+						text = format!("<i>{text}</i>");
+					}
+					if style.bold {
+						text = format!("<b>{text}</b>");
+					}
+				}
+
+				cues.push(Cue {
+					start,
+					end,
+					settings: placement.to_settings(margins, play_size),
+					text,
+				});
+			},
+			_ => (),
+		}
+	}
+
+	// Events don't have to be in order, but cues do
+	cues.sort_by(|a, b| a.start.total_cmp(&b.start));
+	let mut result = Vec::new();
+	for cue in cues {
+		add_cue(&mut result, cue);
+	}
+	return make_webvtt(&result);
+}
+
+
+/// Get the lowercase names of the fields of a format line like "Layer, Start, End, Style, Text"
+fn parse_format(value: &str) -> Vec<String>
+{
+	return value.split(',').map(|name| name.trim().to_ascii_lowercase()).collect();
+}
+
+
+/// Split the fields of a line, where the last one can have commas since it's the text
+fn split_fields(value: &str, count: usize) -> Vec<&str>
+{
+	return value.splitn(count.max(1), ',').collect();
+}
+
+
+/// Get a field by its lowercase name, which is empty if it's missing
+fn get_field<'a>(format: &[String], fields: &[&'a str], name: &str) -> &'a str
+{
+	return match format.iter().position(|field_name| field_name == name) {
+		Some(i) => fields.get(i).map_or("", |field| field.trim()),
+		None => "",
+	};
+}
+
+
+/// Parse a timestamp like `1:02:03.45` in seconds
+fn parse_timestamp(timestamp: &str) -> Option<f64>
+{
+	let mut parts = timestamp.split(':');
+	let hours: f64 = parts.next()?.parse().ok()?;
+	let minutes: f64 = parts.next()?.parse().ok()?;
+	let seconds: f64 = parts.next()?.parse().ok()?;
+	if parts.next().is_some() {
+		return None;
+	}
+	return Some(hours * 3600.0 + minutes * 60.0 + seconds);
+}
+
+
+/// Convert the text of an event to the text of a cue, where the placement is changed by the override tags
+/// like `{\an8}` and the ones which WebVTT doesn't have are removed, or it's empty if it's a drawing
+fn convert_text(text: &str, placement: &mut Placement) -> String
+{
+	let mut result = String::new();
+	let mut rest = text;
+	let mut open_tags: Vec<char> = Vec::new();
+
+	while let Some(c) = rest.chars().next() {
+		// Override tags like `{\i1\pos(10,20)}`, where text in braces without a backslash is a comment
+		if c == '{' && let Some(end) = rest.find('}') {
+			for tag in rest[1..end].split('\\').skip(1) {
+				let tag = tag.trim();
+				if let Some(alignment) = tag.strip_prefix("an").and_then(|value| value.parse::<u8>().ok()) {
+					placement.alignment = alignment.clamp(1, 9);
+				}
+				else if let Some(alignment) = tag.strip_prefix('a').and_then(|value| value.parse::<u8>().ok()) {
+					placement.alignment = ssa_alignment(alignment);
+				}
+				else if let Some(arguments) = tag.strip_prefix("pos(").map(|arguments| arguments.trim_end_matches(')')) {
+					let mut arguments = arguments.split(',').map(|argument| argument.trim().parse::<f64>());
+					if let (Some(Ok(x)), Some(Ok(y))) = (arguments.next(), arguments.next()) {
+						placement.position = Some((x, y));
+					}
+				}
+				// Skip drawings, which are vector shapes instead of text
+				else if tag.starts_with('p') && tag[1..].parse::<u32>().is_ok_and(|scale| scale > 0) {
+					return String::new();
+				}
+				else if let Some(name @ ('i' | 'b' | 'u')) = tag.chars().next() && tag.len() <= 4 {
+					let value = &tag[1..];
+					let is_on = value.parse::<u32>().is_ok_and(|value| value > 0);
+					if is_on && !open_tags.contains(&name) {
+						result += &format!("<{name}>");
+						open_tags.push(name);
+					}
+					else if (value.is_empty() || value == "0") && let Some(i) = open_tags.iter().position(|&open| open == name) {
+						result += &format!("</{name}>");
+						open_tags.remove(i);
+					}
+				}
+			}
+			rest = &rest[end + 1..];
+			continue;
+		}
+
+		// Line breaks like `\N`, where `\n` only breaks in some wrapping styles, and hard spaces like `\h`
+		if let Some(escaped) = rest.strip_prefix('\\') {
+			match escaped.chars().next() {
+				Some('N') => result.push('\n'),
+				Some('n') => result.push(' '),
+				Some('h') => result.push('\u{a0}'),
+				_ => result.push('\\'),
+			}
+			rest = match escaped.chars().next() {
+				Some('N' | 'n' | 'h') => &escaped[1..],
+				_ => escaped,
+			};
+			continue;
+		}
+
+		match c {
+			'&' => result += "&amp;",
+			'<' => result += "&lt;",
+			'>' => result += "&gt;",
+			_ => result.push(c),
+		}
+		rest = &rest[c.len_utf8()..];
+	}
+
+	for name in open_tags.iter().rev() {
+		result += &format!("</{name}>");
+	}
+
+	// Remove blank lines, since a blank line ends a cue
+	return result.split('\n')
+		.map(str::trim)
+		.filter(|line| !line.is_empty())
+		.collect::<Vec<&str>>()
+		.join("\n");
+}
+
+
+/// Convert an alignment of SSA, which is 1-3 for the bottom, 5-7 for the top, and 9-11 for the middle,
+/// to one like a number pad
+fn ssa_alignment(alignment: u8) -> u8
+{
+	return match alignment {
+		5..=7 => alignment + 2,
+		9..=11 => alignment - 5,
+		_ => alignment,
+	}.clamp(1, 9);
+}
+
+
+/// Format a fraction as a percentage like "12.5" for 0.125
+fn format_percent(fraction: f64) -> String
+{
+	let percent = format!("{:.2}", (fraction * 100.0).clamp(0.0, 100.0));
+	return percent.trim_end_matches('0').trim_end_matches('.').to_string();
+}
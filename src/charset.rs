@@ -0,0 +1,168 @@
+/// Characters 0x80 to 0xFF of Windows-1252, which is for Western European languages and has the letters of ISO-8859-1
+const WINDOWS_1252: [u16; 128] = [
+	0x20AC, 0x0081, 0x201A, 0x0192, 0x201E, 0x2026, 0x2020, 0x2021, 0x02C6, 0x2030, 0x0160, 0x2039, 0x0152, 0x008D, 0x017D, 0x008F,
+	0x0090, 0x2018, 0x2019, 0x201C, 0x201D, 0x2022, 0x2013, 0x2014, 0x02DC, 0x2122, 0x0161, 0x203A, 0x0153, 0x009D, 0x017E, 0x0178,
+	0x00A0, 0x00A1, 0x00A2, 0x00A3, 0x00A4, 0x00A5, 0x00A6, 0x00A7, 0x00A8, 0x00A9, 0x00AA, 0x00AB, 0x00AC, 0x00AD, 0x00AE, 0x00AF,
+	0x00B0, 0x00B1, 0x00B2, 0x00B3, 0x00B4, 0x00B5, 0x00B6, 0x00B7, 0x00B8, 0x00B9, 0x00BA, 0x00BB, 0x00BC, 0x00BD, 0x00BE, 0x00BF,
+	0x00C0, 0x00C1, 0x00C2, 0x00C3, 0x00C4, 0x00C5, 0x00C6, 0x00C7, 0x00C8, 0x00C9, 0x00CA, 0x00CB, 0x00CC, 0x00CD, 0x00CE, 0x00CF,
+	0x00D0, 0x00D1, 0x00D2, 0x00D3, 0x00D4, 0x00D5, 0x00D6, 0x00D7, 0x00D8, 0x00D9, 0x00DA, 0x00DB, 0x00DC, 0x00DD, 0x00DE, 0x00DF,
+	0x00E0, 0x00E1, 0x00E2, 0x00E3, 0x00E4, 0x00E5, 0x00E6, 0x00E7, 0x00E8, 0x00E9, 0x00EA, 0x00EB, 0x00EC, 0x00ED, 0x00EE, 0x00EF,
+	0x00F0, 0x00F1, 0x00F2, 0x00F3, 0x00F4, 0x00F5, 0x00F6, 0x00F7, 0x00F8, 0x00F9, 0x00FA, 0x00FB, 0x00FC, 0x00FD, 0x00FE, 0x00FF,
+];
+
+/// Characters 0x80 to 0xFF of Windows-1250, which is for Central European languages
+const WINDOWS_1250: [u16; 128] = [
+	0x20AC, 0x0081, 0x201A, 0x0083, 0x201E, 0x2026, 0x2020, 0x2021, 0x0088, 0x2030, 0x0160, 0x2039, 0x015A, 0x0164, 0x017D, 0x0179,
+	0x0090, 0x2018, 0x2019, 0x201C, 0x201D, 0x2022, 0x2013, 0x2014, 0x0098, 0x2122, 0x0161, 0x203A, 0x015B, 0x0165, 0x017E, 0x017A,
+	0x00A0, 0x02C7, 0x02D8, 0x0141, 0x00A4, 0x0104, 0x00A6, 0x00A7, 0x00A8, 0x00A9, 0x015E, 0x00AB, 0x00AC, 0x00AD, 0x00AE, 0x017B,
+	0x00B0, 0x00B1, 0x02DB, 0x0142, 0x00B4, 0x00B5, 0x00B6, 0x00B7, 0x00B8, 0x0105, 0x015F, 0x00BB, 0x013D, 0x02DD, 0x013E, 0x017C,
+	0x0154, 0x00C1, 0x00C2, 0x0102, 0x00C4, 0x0139, 0x0106, 0x00C7, 0x010C, 0x00C9, 0x0118, 0x00CB, 0x011A, 0x00CD, 0x00CE, 0x010E,
+	0x0110, 0x0143, 0x0147, 0x00D3, 0x00D4, 0x0150, 0x00D6, 0x00D7, 0x0158, 0x016E, 0x00DA, 0x0170, 0x00DC, 0x00DD, 0x0162, 0x00DF,
+	0x0155, 0x00E1, 0x00E2, 0x0103, 0x00E4, 0x013A, 0x0107, 0x00E7, 0x010D, 0x00E9, 0x0119, 0x00EB, 0x011B, 0x00ED, 0x00EE, 0x010F,
+	0x0111, 0x0144, 0x0148, 0x00F3, 0x00F4, 0x0151, 0x00F6, 0x00F7, 0x0159, 0x016F, 0x00FA, 0x0171, 0x00FC, 0x00FD, 0x0163, 0x02D9,
+];
+
+/// Characters 0x80 to 0xFF of ISO-8859-2, which is for Central European languages
+const ISO_8859_2: [u16; 128] = [
+	0x0080, 0x0081, 0x0082, 0x0083, 0x0084, 0x0085, 0x0086, 0x0087, 0x0088, 0x0089, 0x008A, 0x008B, 0x008C, 0x008D, 0x008E, 0x008F,
+	0x0090, 0x0091, 0x0092, 0x0093, 0x0094, 0x0095, 0x0096, 0x0097, 0x0098, 0x0099, 0x009A, 0x009B, 0x009C, 0x009D, 0x009E, 0x009F,
+	0x00A0, 0x0104, 0x02D8, 0x0141, 0x00A4, 0x013D, 0x015A, 0x00A7, 0x00A8, 0x0160, 0x015E, 0x0164, 0x0179, 0x00AD, 0x017D, 0x017B,
+	0x00B0, 0x0105, 0x02DB, 0x0142, 0x00B4, 0x013E, 0x015B, 0x02C7, 0x00B8, 0x0161, 0x015F, 0x0165, 0x017A, 0x02DD, 0x017E, 0x017C,
+	0x0154, 0x00C1, 0x00C2, 0x0102, 0x00C4, 0x0139, 0x0106, 0x00C7, 0x010C, 0x00C9, 0x0118, 0x00CB, 0x011A, 0x00CD, 0x00CE, 0x010E,
+	0x0110, 0x0143, 0x0147, 0x00D3, 0x00D4, 0x0150, 0x00D6, 0x00D7, 0x0158, 0x016E, 0x00DA, 0x0170, 0x00DC, 0x00DD, 0x0162, 0x00DF,
+	0x0155, 0x00E1, 0x00E2, 0x0103, 0x00E4, 0x013A, 0x0107, 0x00E7, 0x010D, 0x00E9, 0x0119, 0x00EB, 0x011B, 0x00ED, 0x00EE, 0x010F,
+	0x0111, 0x0144, 0x0148, 0x00F3, 0x00F4, 0x0151, 0x00F6, 0x00F7, 0x0159, 0x016F, 0x00FA, 0x0171, 0x00FC, 0x00FD, 0x0163, 0x02D9,
+];
+
+/// Characters 0x80 to 0xFF of Windows-1251, which is for Cyrillic languages
+const WINDOWS_1251: [u16; 128] = [
+	0x0402, 0x0403, 0x201A, 0x0453, 0x201E, 0x2026, 0x2020, 0x2021, 0x20AC, 0x2030, 0x0409, 0x2039, 0x040A, 0x040C, 0x040B, 0x040F,
+	0x0452, 0x2018, 0x2019, 0x201C, 0x201D, 0x2022, 0x2013, 0x2014, 0x0098, 0x2122, 0x0459, 0x203A, 0x045A, 0x045C, 0x045B, 0x045F,
+	0x00A0, 0x040E, 0x045E, 0x0408, 0x00A4, 0x0490, 0x00A6, 0x00A7, 0x0401, 0x00A9, 0x0404, 0x00AB, 0x00AC, 0x00AD, 0x00AE, 0x0407,
+	0x00B0, 0x00B1, 0x0406, 0x0456, 0x0491, 0x00B5, 0x00B6, 0x00B7, 0x0451, 0x2116, 0x0454, 0x00BB, 0x0458, 0x0405, 0x0455, 0x0457,
+	0x0410, 0x0411, 0x0412, 0x0413, 0x0414, 0x0415, 0x0416, 0x0417, 0x0418, 0x0419, 0x041A, 0x041B, 0x041C, 0x041D, 0x041E, 0x041F,
+	0x0420, 0x0421, 0x0422, 0x0423, 0x0424, 0x0425, 0x0426, 0x0427, 0x0428, 0x0429, 0x042A, 0x042B, 0x042C, 0x042D, 0x042E, 0x042F,
+	0x0430, 0x0431, 0x0432, 0x0433, 0x0434, 0x0435, 0x0436, 0x0437, 0x0438, 0x0439, 0x043A, 0x043B, 0x043C, 0x043D, 0x043E, 0x043F,
+	0x0440, 0x0441, 0x0442, 0x0443, 0x0444, 0x0445, 0x0446, 0x0447, 0x0448, 0x0449, 0x044A, 0x044B, 0x044C, 0x044D, 0x044E, 0x044F,
+];
+
+/// Encodings which are guessed from the text if the language doesn't have one, where the first one wins a tie
+const GUESSED_ENCODINGS: [&[u16; 128]; 3] = [&WINDOWS_1252, &WINDOWS_1250, &ISO_8859_2];
+
+
+/// Decode text in UTF-8 or UTF-16 with a byte order mark, UTF-16 without one, or a legacy encoding like Windows-1252,
+/// where the language like "ru" helps to guess the legacy encoding
+pub fn decode_text(bytes: &[u8], language: &str) -> String
+{
+	// Byte order mark
+	if let Some(utf8) = bytes.strip_prefix(b"\xef\xbb\xbf") {
+		return String::from_utf8_lossy(utf8).into_owned();
+	}
+	if let Some(utf16) = bytes.strip_prefix(b"\xff\xfe") {
+		return decode_utf16(utf16, u16::from_le_bytes);
+	}
+	if let Some(utf16) = bytes.strip_prefix(b"\xfe\xff") {
+		return decode_utf16(utf16, u16::from_be_bytes);
+	}
+
+	// UTF-16 without a byte order mark, where most of the text is ASCII so every other byte is 0
+	let sample = &bytes[..bytes.len().min(1024) & !1];
+	let pairs = sample.len() / 2;
+	if pairs >= 2 {
+		let zeros = |offset: usize| sample.iter().skip(offset).step_by(2).filter(|&&byte| byte == 0).count();
+		if zeros(1) * 2 > pairs && zeros(0) == 0 {
+			return decode_utf16(bytes, u16::from_le_bytes);
+		}
+		if zeros(0) * 2 > pairs && zeros(1) == 0 {
+			return decode_utf16(bytes, u16::from_be_bytes);
+		}
+	}
+
+	if let Ok(text) = core::str::from_utf8(bytes) {
+		return text.to_string();
+	}
+
+	// Use the encoding of the language if it has one
+	match language {
+		"be" | "bg" | "kk" | "mk" | "ru" | "sr" | "uk" => return decode_single_byte(bytes, &WINDOWS_1251),
+		"bs" | "cs" | "hr" | "hu" | "pl" | "ro" | "sk" | "sl" | "sq" => return decode_single_byte(bytes, &WINDOWS_1250),
+		_ => (),
+	}
+
+	// Cyrillic has words of only non-ASCII letters, unlike the languages with the Latin alphabet
+	let high_bytes = bytes.iter().filter(|&&byte| byte >= 0xc0).count();
+	let high_pairs = bytes.windows(2).filter(|pair| pair[0] >= 0xc0 && pair[1] >= 0xc0).count();
+	if high_pairs * 2 > high_bytes {
+		return decode_single_byte(bytes, &WINDOWS_1251);
+	}
+
+	// Choose the encoding which looks the most like words
+	let mut result = String::new();
+	let mut best_score = i64::MIN;
+	for encoding in GUESSED_ENCODINGS {
+		let text = decode_single_byte(bytes, encoding);
+		let score = score_words(&text);
+		if score > best_score {
+			result = text;
+			best_score = score;
+		}
+	}
+	return result;
+}
+
+
+/// Decode UTF-16 given a function to read each pair of bytes
+fn decode_utf16(bytes: &[u8], read: fn([u8; 2]) -> u16) -> String
+{
+	let units: Vec<u16> = bytes.chunks_exact(2)
+		.map(|pair| read([pair[0], pair[1]]))
+		.collect();
+	return String::from_utf16_lossy(&units);
+}
+
+
+/// Decode text in an encoding with 1 byte per character, which is ASCII before 0x80
+fn decode_single_byte(bytes: &[u8], encoding: &[u16; 128]) -> String
+{
+	return bytes.iter()
+		.map(|&byte| match byte {
+			0..0x80 => byte as char,
+			_ => char::from_u32(encoding[byte as usize - 0x80] as u32).unwrap_or(char::REPLACEMENT_CHARACTER),
+		})
+		.collect();
+}
+
+
+/// Score how much the non-ASCII characters look like parts of words, where letters next to letters count for it,
+/// and symbols next to letters, controls, and capital letters after lowercase ones count against it
+fn score_words(text: &str) -> i64
+{
+	let chars: Vec<char> = text.chars().collect();
+	let mut result = 0;
+	for (i, &c) in chars.iter().enumerate() {
+		let previous = match i {
+			0 => ' ',
+			_ => chars[i - 1],
+		};
+		let next = chars.get(i + 1).copied().unwrap_or(' ');
+
+		if c.is_uppercase() && previous.is_lowercase() {
+			result -= 3;
+		}
+		if c.is_ascii() {
+			continue;
+		}
+		if c.is_control() {
+			result -= 5;
+		}
+		else if c.is_alphabetic() {
+			if previous.is_alphabetic() || next.is_alphabetic() {
+				result += 1;
+			}
+		}
+		else if previous.is_alphabetic() || next.is_alphabetic() {
+			result -= 1;
+		}
+	}
+	return result;
+}
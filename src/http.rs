@@ -1,105 +1,250 @@
-use core::mem::MaybeUninit;
 use std::borrow::Cow;
 use std::fs::OpenOptions;
-use std::io::ErrorKind::InvalidInput;
-use std::io::IoSlice;
 use std::io::Read;
-use std::io::Seek;
-use std::io::Write;
+use std::net::SocketAddr;
 use std::net::TcpStream;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
 
+use crate::arguments::*;
+use crate::ass::*;
+use crate::charset::*;
 use crate::languages::*;
 use crate::link_info::*;
+use crate::metadata::*;
+use crate::mp4::*;
+use crate::mp4_remux::*;
+use crate::mp4_text::*;
 use crate::name_parts::*;
+use crate::print::*;
 use crate::request::*;
+use crate::response::*;
+use crate::server::*;
+use crate::shutdown::*;
+use crate::srt::*;
 use crate::status::*;
+use crate::validator::*;
+use crate::video_formats::*;
 
 use Status::*;
 
 
 /// Maximum size in bytes of an incoming HTTP request
-const REQUEST_SIZE: usize = 4096;
+pub const REQUEST_SIZE: usize = 4096;
 
-// Size in bytes of a portion of a video (don't exceed this)
-const VIDEO_BUFFER_SIZE: usize = 1 << 21;
+/// Methods which are handled, for the `Allow` header
+const ALLOWED_METHODS: &str = "GET, HEAD, OPTIONS";
 
+/// Query key to get the cover art in an MP4 file instead of the video, like "/Movie.mp4?cover"
+const COVER_QUERY: &str = "cover";
+/// Query key to download a time range of an MP4 file as its own file, like "/Movie.mp4?clip=1:30-2:00"
+const CLIP_QUERY: &str = "clip";
+/// Query key to get an MP4 file as a fragmented MP4 file for HLS, like "/Movie.mp4?hls"
+const HLS_QUERY: &str = "hls";
+/// Query key to get a text track of an MP4 file as WebVTT given its index, like "/Movie.mp4?subtitles=2"
+const SUBTITLES_QUERY: &str = "subtitles";
 
-/// Read from the incoming request and either respond or shut it down
-pub fn handle_request(root_folder: &str, mut stream: TcpStream)
+/// Extension to get the audio of an MP4 file instead of the video, like "/Movie.m4a" for "/Movie.mp4"
+const AUDIO_EXTENSION: &str = ".m4a";
+/// Extension to get an HLS playlist of an MP4 file, like "/Movie.m3u8" for "/Movie.mp4"
+const PLAYLIST_EXTENSION: &str = ".m3u8";
+
+
+/// Read requests from the connection and respond to each one until it's closed,
+/// which blocks the thread for the whole connection
+pub fn handle_connection(config: &Config, handle: ConnectionHandle, mut stream: TcpStream)
 {
 	let stream = &mut stream;
 
-	// Read all bytes into the buffer or fail
-	#[allow(invalid_value)]
-	let mut request: [u8; REQUEST_SIZE] = unsafe { MaybeUninit::uninit().assume_init() };
-	let request_length = match stream.read(&mut request) {
-		Ok(request_length @ 0..REQUEST_SIZE) => request_length,
-		_ => return,
+	// Close the connection if the client doesn't send anything or read the response for a while
+	if stream.set_read_timeout(Some(config.idle_timeout)).is_err() || stream.set_write_timeout(Some(config.idle_timeout)).is_err() {
+		return;
+	}
+
+	let mut buffer = [0; REQUEST_SIZE];
+	let mut length = 0;
+
+	loop {
+		// Respond to each complete request in the buffer, since they can be pipelined
+		while let Some(head_end) = find_head_end(&buffer[..length]) {
+			let keep_alive = match Request::parse(&buffer[..head_end]) {
+				None => {
+					let _ = write_response(&handle, stream, Response::new(BadRequest), true, false);
+					return;
+				},
+				Some(request) => {
+					handle.set_path(&request.path);
+					let response = respond(config, &request);
+					log_response(config, handle.address, &request, &response);
+					let send_body = request.method != Method::Head;
+					let keep_alive = request.keep_alive && !is_shutting_down();
+					if write_response(&handle, stream, response, send_body, keep_alive).is_err() {
+						return;
+					}
+					keep_alive
+				},
+			};
+			if !keep_alive {
+				return;
+			}
+
+			// Move the next request to the beginning
+			buffer.copy_within(head_end..length, 0);
+			length -= head_end;
+		}
+
+		// Fail if the request is too big
+		if length == REQUEST_SIZE {
+			let _ = write_response(&handle, stream, Response::new(BadRequest), true, false);
+			return;
+		}
+
+		// Read more bytes, otherwise the client closed the connection or was idle
+		length += match stream.read(&mut buffer[length..]) {
+			Ok(0) | Err(_) => return,
+			Ok(size) => size,
+		};
+		handle.touch();
+	}
+}
+
+
+/// Write a response in portions, so the registry shows the progress of long downloads
+fn write_response(handle: &ConnectionHandle, stream: &mut TcpStream, response: Response, send_body: bool, keep_alive: bool) -> std::io::Result<()>
+{
+	/// Maximum bytes written before updating the registry
+	const PROGRESS_SIZE: u64 = 1 << 22;
+
+	let mut writer = ResponseWriter::new(response, send_body, keep_alive);
+	loop {
+		let sent = writer.sent;
+		let result = writer.write(stream, PROGRESS_SIZE);
+		handle.add_bytes_sent(writer.sent - sent);
+		match result? {
+			true => return Ok(()),
+			false => (),
+		}
+	}
+}
+
+
+/// Tell a client to try again later since there are too many connections, then close it
+pub fn reject_connection(mut stream: TcpStream)
+{
+	let mut writer = ResponseWriter::new(Response::new(ServiceUnavailable), true, false);
+	let _ = writer.write(&mut stream, u64::MAX);
+}
+
+
+/// Get the position after the blank line which ends the request head
+pub fn find_head_end(buffer: &[u8]) -> Option<usize>
+{
+	const HEAD_END: &[u8] = b"\r\n\r\n";
+	return buffer.windows(HEAD_END.len())
+		.position(|window| window == HEAD_END)
+		.map(|i| i + HEAD_END.len());
+}
+
+
+/// Make a response given the request's method
+pub fn respond(config: &Config, request: &Request) -> Response
+{
+	return match request.method {
+		// Get the content, where HEAD only writes the headers of the same response
+		Method::Get | Method::Head => respond_file(&config.folders, request),
+		// Describe the allowed methods, including for cross-origin preflight requests
+		Method::Options => {
+			let mut response = Response::new(NoContent);
+			response.add_header("Allow", ALLOWED_METHODS);
+			response.add_header("Access-Control-Allow-Origin", "*");
+			response.add_header("Access-Control-Allow-Methods", ALLOWED_METHODS);
+			response.add_header("Access-Control-Allow-Headers", "If-Modified-Since, If-None-Match, If-Range, Range");
+			response
+		},
+		Method::Other => {
+			let mut response = Response::new(MethodNotAllowed);
+			response.add_header("Allow", ALLOWED_METHODS);
+			response
+		},
 	};
+}
 
-	// Truncate the request slice
-	let request = &request[0..request_length];
 
-	// Parse the Request and respond
-	match Request::parse(&request) {
-		None => respond_status(stream, BadRequest),
-		Some(request) => respond_file(root_folder, stream, request),
+/// Print the client's request and the response status if requests are logged
+pub fn log_response(config: &Config, address: SocketAddr, request: &Request, response: &Response)
+{
+	if config.log < Log::Requests {
+		return;
 	}
+
+	// Show IPv4 clients of dual-stack listeners like 127.0.0.1 instead of ::ffff:127.0.0.1
+	let address = SocketAddr::new(address.ip().to_canonical(), address.port());
+	print(&format!("{address} {} {} {}\n", request.method.to_str(), request.path, response.status.to_str()));
 }
 
 
-/// Write a response given a file path
-fn respond_file(root_folder: &str, stream: &mut TcpStream, request: Request)
+/// Make a response given a file path in one of the root folders
+fn respond_file(root_folders: &[String], request: &Request) -> Response
 {
 	const CONTENT_TYPE_CSS:   &str = "text/css";
 	const CONTENT_TYPE_HTML:  &str = "text/html";
 	const CONTENT_TYPE_JPG:   &str = "image/jpeg";
 	const CONTENT_TYPE_JS:    &str = "text/javascript";
 	const CONTENT_TYPE_JSON:  &str = "application/json";
-	const CONTENT_TYPE_MP4:   &str = "video/mp4";
+	const CONTENT_TYPE_M3U8:  &str = "application/vnd.apple.mpegurl";
+	const CONTENT_TYPE_M4A:   &str = "audio/mp4";
 	const CONTENT_TYPE_PNG:   &str = "image/png";
 	const CONTENT_TYPE_SVG:   &str = "image/svg+xml";
-	const CONTENT_TYPE_VTT:   &str = "text/vtt";
+	const CONTENT_TYPE_VTT:   &str = "text/vtt; charset=utf-8";
 	const CONTENT_TYPE_WEBP:  &str = "image/webp";
 	const CONTENT_TYPE_WOFF2: &str = "font/woff2";
 
-	let mut buffer = Vec::new();
-
-	let (content_type, content) = match request.path.as_str() {
+	let (content_type, content, validator) = match request.path.as_str() {
 		"/alata.woff2" => (
 			CONTENT_TYPE_WOFF2,
-			include_bytes!("../res/alata.woff2").as_slice()),
+			Part::Static(include_bytes!("../res/alata.woff2").as_slice()),
+			None),
 		"/logo.svg" => (
 			CONTENT_TYPE_SVG,
-			include_str!("../res/logo.svg").as_bytes()),
+			Part::Static(include_str!("../res/logo.svg").as_bytes()),
+			None),
 		"/logo_circle.svg" => (
 			CONTENT_TYPE_SVG,
-			include_str!("../res/logo_circle.svg").as_bytes()),
+			Part::Static(include_str!("../res/logo_circle.svg").as_bytes()),
+			None),
 		"/manifest.json" => (
 			CONTENT_TYPE_JSON,
-			include_str!("../res/manifest.json").as_bytes()),
+			Part::Static(include_str!("../res/manifest.json").as_bytes()),
+			None),
 		"/saira_condensed.woff2" => (
 			CONTENT_TYPE_WOFF2,
-			include_bytes!("../res/saira_condensed.woff2").as_slice()),
+			Part::Static(include_bytes!("../res/saira_condensed.woff2").as_slice()),
+			None),
 		"/style.css" => (
 			CONTENT_TYPE_CSS,
-			include_str!("../res/style.css").as_bytes()),
+			Part::Static(include_str!("../res/style.css").as_bytes()),
+			None),
 		"/video.js" => (
 			CONTENT_TYPE_JS,
-			include_str!("../res/video.js").as_bytes()),
+			Part::Static(include_str!("../res/video.js").as_bytes()),
+			None),
 		"/videos.js" => (
 			CONTENT_TYPE_JS,
-			include_str!("../res/videos.js").as_bytes()),
+			Part::Static(include_str!("../res/videos.js").as_bytes()),
+			None),
 		client_path => {
 			if has_parent_dir(client_path) {
-				return respond_status(stream, NotFound);
+				return Response::new(NotFound);
 			}
 
-			let full_path = format!("{root_folder}{client_path}");
-
-			match full_path.ends_with("/") {
-				// Generated HTML
+			match client_path.ends_with("/") {
+				// Generated HTML combining the folder in each root folder
 				true => {
+					let full_folders: Vec<String> = root_folders.iter()
+						.map(|root_folder| format!("{root_folder}{client_path}"))
+						.collect();
+
 					let mut video_name = "";
 					for query in &request.query {
 						if query.key != "watch" {
@@ -108,179 +253,515 @@ fn respond_file(root_folder: &str, stream: &mut TcpStream, request: Request)
 						video_name = query.value.as_str();
 					}
 
-					if video_name.is_empty() {
-						(CONTENT_TYPE_HTML, make_html_videos(&full_path, client_path, &mut buffer))
-					}
-					else {
-						(CONTENT_TYPE_HTML, make_html_video(&full_path, video_name, &mut buffer))
-					}
+					let mut buffer = Vec::new();
+					let content = match video_name.is_empty() {
+						true => make_html_videos(&full_folders, client_path, &mut buffer),
+						false => make_html_video(&full_folders, video_name, &mut buffer),
+					};
+
+					// The HTML is the same if the folder is the same, but it isn't the same bytes
+					// if something like the order of the files changes
+					let validator = Validator::from_content(content, true);
+					(CONTENT_TYPE_HTML, Part::Bytes(buffer), Some(validator))
 				},
-				// File from the filesystem
+				// File from the filesystem in the first root folder which has it
 				false => {
+					let full_path = root_folders.iter()
+						.map(|root_folder| format!("{root_folder}{client_path}"))
+						.find(|full_path| Path::new(full_path).is_file())
+						.unwrap_or_default();
+
+					// Audio of an MP4 video, unless the folder has the audio file
+					if full_path.is_empty() && let Some(client_basename) = client_path.strip_suffix(AUDIO_EXTENSION) {
+						return respond_audio(root_folders, request, client_basename);
+					}
+
+					// HLS playlist of an MP4 video, unless the folder has the playlist file
+					if full_path.is_empty() && let Some(client_basename) = client_path.strip_suffix(PLAYLIST_EXTENSION) {
+						return respond_playlist(root_folders, request, client_basename);
+					}
+
 					let content_type = match get_last_extension(&full_path) {
 						".jpg" | ".jpeg" => CONTENT_TYPE_JPG,
 						".js" => CONTENT_TYPE_JS,
+						PLAYLIST_EXTENSION => CONTENT_TYPE_M3U8,
 						".png" => CONTENT_TYPE_PNG,
 						".webp" => CONTENT_TYPE_WEBP,
-						".vtt" => CONTENT_TYPE_VTT,
-						".mp4" => {
-							// Get the file or fail
-							let mut file = match OpenOptions::new().read(true).open(&full_path) {
-								Ok(file) => file,
-								Err(_) => return respond_status(stream, NotFound),
-							};
-
-							// Get the file size or fail
-							let total_size = match file.metadata() {
-								Ok(metadata) => metadata.len(),
-								Err(_) => return respond_status(stream, InternalServerError),
-							};
-							let total_size = match total_size.try_into() {
-								Ok(total_size) => total_size,
-								Err(_) => return respond_status(stream, InternalServerError),
-							};
-
-							// Determine whether to download or stream
-							return match request.range_start {
-								// Download
-								None => {
-									// Allocate space or fail
-									let mut buffer = Vec::new();
-									let buffer_size = match total_size {
-										0..=VIDEO_BUFFER_SIZE => total_size,
-										_ => VIDEO_BUFFER_SIZE,
-									};
-									if buffer.try_reserve(buffer_size).is_err() {
-										return respond_status(stream, InternalServerError);
-									}
-
-									// Start the response
-									if stream.write_vectored(&[
-										IoSlice::new(b"HTTP/1.1 200 Ok\r\nContent-Length: "),
-										IoSlice::new(total_size.to_string().as_bytes()),
-										IoSlice::new(b"\r\nContent-Type: video/mp4\r\n\r\n"),
-									]).is_err() {
-										return;
-									}
-
-									loop {
-										// Read the bytes or fail
-										unsafe { buffer.set_len(buffer_size) }
-										match file.read(&mut buffer) {
-											Ok(size) => unsafe { buffer.set_len(size) },
-											Err(_) => return respond_status(stream, InternalServerError),
-										}
-
-										// Continue the response
-										if stream.write_all(&buffer).is_err() {
-											return;
-										}
-									}
-								},
-								// Stream
-								Some(begin) => {
-									// Seek or fail
-									if begin > 0 {
-										let seek = match begin.try_into() {
-											Ok(seek) => seek,
-											Err(_) => return respond_status(stream, InternalServerError),
-										};
-										if begin > total_size {
-											return respond_status(stream, RangeNotSatisfiable);
-										}
-										match file.seek_relative(seek) {
-											Ok(()) => (),
-											Err(error) => {
-												let status_code = match error.kind() {
-													InvalidInput => RangeNotSatisfiable,
-													_ => InternalServerError,
-												};
-												return respond_status(stream, status_code);
-											},
-										}
-									}
-
-									// Allocate space or fail
-									let mut buffer = Vec::new();
-									let buffer_size = match total_size - begin {
-										buffer_size @ 0..=VIDEO_BUFFER_SIZE => buffer_size,
-										_ => VIDEO_BUFFER_SIZE,
-									};
-									if buffer.try_reserve(buffer_size).is_err() {
-										return respond_status(stream, InternalServerError);
-									}
-
-									// Read the bytes or fail
-									unsafe { buffer.set_len(buffer_size) }
-									match file.read(&mut buffer) {
-										Ok(size) => unsafe { buffer.set_len(size) },
-										Err(_) => return respond_status(stream, InternalServerError),
-									}
-
-									// Respond as partial content
-									let end = begin + buffer.len() - 1;
-									respond_partial_content(stream, CONTENT_TYPE_MP4, &buffer, begin, end, total_size);
-								},
-							};
+						".vtt" | ".srt" | ".ass" | ".ssa" => CONTENT_TYPE_VTT,
+						extension if MP4_EXTENSIONS.contains(&extension) && request.query.iter().any(|query| query.key == COVER_QUERY) => {
+							return respond_cover(request, &full_path);
+						},
+						extension if MP4_EXTENSIONS.contains(&extension) && let Some(clip) = request.query.iter().find(|query| query.key == CLIP_QUERY) => {
+							return respond_clip(request, &full_path, &clip.value);
+						},
+						extension if MP4_EXTENSIONS.contains(&extension) && let Some(track) = request.query.iter().find(|query| query.key == SUBTITLES_QUERY) => {
+							return respond_text_track(request, &full_path, &track.value);
+						},
+						extension if MP4_EXTENSIONS.contains(&extension) && request.query.iter().any(|query| query.key == HLS_QUERY) => {
+							return respond_fragmented(request, &full_path);
+						},
+						AUDIO_EXTENSION => return respond_video(request, &full_path, CONTENT_TYPE_M4A),
+						extension => match video_content_type(extension) {
+							Some(content_type) => return respond_video(request, &full_path, content_type),
+							None => return Response::new(NotFound),
 						},
-						_ => return respond_status(stream, NotFound),
 					};
 
-					buffer = match std::fs::read(&full_path) {
-						Ok(buffer) => buffer,
-						Err(_) => return respond_status(stream, NotFound),
+					// Get the file and its metadata or fail
+					let mut file = match OpenOptions::new().read(true).open(&full_path) {
+						Ok(file) => file,
+						Err(_) => return Response::new(NotFound),
 					};
+					let metadata = match file.metadata() {
+						Ok(metadata) => metadata,
+						Err(_) => return Response::new(InternalServerError),
+					};
+
+					// Skip reading if the client has the file
+					let validator = Validator::from_metadata(&metadata);
+					if validator.is_not_modified(request) {
+						return respond_not_modified(&validator);
+					}
+
+					let mut buffer = Vec::new();
+					if file.read_to_end(&mut buffer).is_err() {
+						return Response::new(InternalServerError);
+					}
+
+					// Convert subtitles to UTF-8 and the other formats to WebVTT, since browsers only show WebVTT,
+					// where the language like ".ru.srt" helps to guess the character set
+					if content_type == CONTENT_TYPE_VTT {
+						let file_name = Path::new(&full_path).file_name().and_then(|name| name.to_str()).unwrap_or_default();
+						let extension = NameParts::new(file_name).extension;
+						let language = extension.rsplit('.')
+							.nth(1)
+							.and_then(LanguageTag::parse)
+							.map_or("", |tag| language_code_to_abbreviation(tag.language));
+						let text = decode_text(&buffer, language);
+						buffer = match get_last_extension(&full_path) {
+							".srt" => srt_to_webvtt(&text),
+							".ass" | ".ssa" => ass_to_webvtt(&text),
+							_ => text,
+						}.into_bytes();
+					}
 
-					(content_type, buffer.as_slice())
+					(content_type, Part::Bytes(buffer), Some(validator))
 				}
 			}
 		},
 	};
 
-	respond_status_and_content(stream, Okay, content_type, content);
+	// Skip the response if the client has the content
+	let validator = match (validator, &content) {
+		(Some(validator), _) => validator,
+		(None, Part::Static(bytes)) => Validator::from_content(bytes, false),
+		(None, _) => return Response::new(InternalServerError),
+	};
+	if validator.is_not_modified(request) {
+		return respond_not_modified(&validator);
+	}
+
+	return respond_content(content_type, content, &validator);
+}
+
+
+/// Stream a video file, or the requested ranges of it
+fn respond_video(request: &Request, full_path: &str, content_type: &'static str) -> Response
+{
+	// Get the file or fail
+	let file = match OpenOptions::new().read(true).open(full_path) {
+		Ok(file) => file,
+		Err(_) => return Response::new(NotFound),
+	};
+
+	// Get the file size or fail
+	let metadata = match file.metadata() {
+		Ok(metadata) => metadata,
+		Err(_) => return Response::new(InternalServerError),
+	};
+
+	// Skip the response if the client has the file
+	let validator = Validator::from_metadata(&metadata);
+	if validator.is_not_modified(request) {
+		return respond_not_modified(&validator);
+	}
+
+	// Download the whole file if the client's partial file isn't current
+	let ranges = match validator.allows_ranges(request) {
+		true => request.ranges.as_slice(),
+		false => &[],
+	};
+
+	// Put the movie box of an MP4 file before the media data if it's at the end, so it can play while downloading
+	let file = Arc::new(file);
+	let fast_start = match MP4_EXTENSIONS.contains(&get_last_extension(full_path)) {
+		true => FastStart::read(Path::new(full_path)),
+		false => None,
+	};
+	let content = match fast_start {
+		Some(layout) => vec![
+			Part::File { file: Arc::clone(&file), begin: 0, length: layout.insert_at },
+			Part::Shared { bytes: Arc::clone(&layout.movie_box), begin: 0, length: layout.movie_box.len() },
+			Part::File { file: Arc::clone(&file), begin: layout.insert_at, length: layout.movie.offset - layout.insert_at },
+			Part::File { file, begin: layout.movie.offset + layout.movie.size, length: metadata.len() - layout.movie.offset - layout.movie.size },
+		],
+		None => vec![Part::File { file, begin: 0, length: metadata.len() }],
+	};
+
+	return respond_ranges(content, content_type, ranges, validator);
+}
+
+
+/// Respond with the cover art in an MP4 file
+fn respond_cover(request: &Request, full_path: &str) -> Response
+{
+	let info = match Mp4Info::read(Path::new(full_path)) {
+		Some(info) => info,
+		None => return Response::new(NotFound),
+	};
+	let cover = match &info.cover {
+		Some(cover) => cover,
+		None => return Response::new(NotFound),
+	};
+
+	// Read the image or fail
+	let mut file = match OpenOptions::new().read(true).open(full_path) {
+		Ok(file) => file,
+		Err(_) => return Response::new(NotFound),
+	};
+	let image = match cover.read(&mut file) {
+		Ok(image) => image,
+		Err(_) => return Response::new(InternalServerError),
+	};
+
+	// Skip the response if the client has the image
+	let validator = Validator::from_content(&image, false);
+	if validator.is_not_modified(request) {
+		return respond_not_modified(&validator);
+	}
+
+	return respond_content(cover.content_type, Part::Bytes(image), &validator);
+}
+
+
+/// Download a time range of an MP4 file as a standalone MP4 file, which starts at the keyframe before the start time
+fn respond_clip(request: &Request, full_path: &str, clip: &str) -> Response
+{
+	let (start, end) = match parse_time_range(clip) {
+		Some(times) => times,
+		None => return Response::new(BadRequest),
+	};
+
+	let options = RemuxOptions {
+		start,
+		end,
+		kinds: &[TrackKind::Video, TrackKind::Audio, TrackKind::Text],
+		brand: None,
+	};
+
+	let file_name = Path::new(full_path).file_name().and_then(|name| name.to_str()).unwrap_or_default();
+	let basename = NameParts::new(file_name).basename.replace('"', "'");
+	let clip_name = clip.replace(':', ".");
+
+	let mut response = respond_remux(request, full_path, &options, "video/mp4", clip);
+	response.add_header("Content-Disposition", &format!("attachment; filename=\"{basename} {clip_name}.mp4\""));
+	return response;
+}
+
+
+/// Stream the audio tracks of an MP4 video as an M4A file, given the path without the extension like "/Movie"
+fn respond_audio(root_folders: &[String], request: &Request, client_basename: &str) -> Response
+{
+	let full_path = match find_mp4(root_folders, client_basename) {
+		Some(full_path) => full_path,
+		None => return Response::new(NotFound),
+	};
+
+	let options = RemuxOptions {
+		start: 0.0,
+		end: None,
+		kinds: &[TrackKind::Audio],
+		brand: Some(b"M4A "),
+	};
+	return respond_remux(request, &full_path, &options, "audio/mp4", AUDIO_EXTENSION);
+}
+
+
+/// Respond with an HLS playlist of an MP4 video, given the path without the extension like "/Movie"
+fn respond_playlist(root_folders: &[String], request: &Request, client_basename: &str) -> Response
+{
+	let full_path = match find_mp4(root_folders, client_basename) {
+		Some(full_path) => full_path,
+		None => return Response::new(NotFound),
+	};
+
+	// Skip the response if the client has the playlist
+	let validator = match std::fs::metadata(&full_path) {
+		Ok(metadata) => Validator::from_metadata(&metadata).with_variant(PLAYLIST_EXTENSION),
+		Err(_) => return Response::new(InternalServerError),
+	};
+	if validator.is_not_modified(request) {
+		return respond_not_modified(&validator);
+	}
+
+	let layout = match Fragmented::read(Path::new(&full_path)) {
+		Some(layout) => layout,
+		None => return Response::new(NotFound),
+	};
+
+	// The segments are ranges of the fragmented file next to the playlist
+	let file_name = Path::new(&full_path).file_name().and_then(|name| name.to_str()).unwrap_or_default();
+	let uri = format!("{}?{HLS_QUERY}", percent_encode(file_name));
+
+	return respond_content("application/vnd.apple.mpegurl", Part::Bytes(layout.playlist(&uri).into_bytes()), &validator);
+}
+
+
+/// Stream an MP4 file as a fragmented MP4 file for HLS, or the requested ranges of it
+fn respond_fragmented(request: &Request, full_path: &str) -> Response
+{
+	// Get the file and its metadata or fail
+	let file = match OpenOptions::new().read(true).open(full_path) {
+		Ok(file) => file,
+		Err(_) => return Response::new(NotFound),
+	};
+	let metadata = match file.metadata() {
+		Ok(metadata) => metadata,
+		Err(_) => return Response::new(InternalServerError),
+	};
+
+	// Skip the response if the client has the fragmented file
+	let validator = Validator::from_metadata(&metadata).with_variant(HLS_QUERY);
+	if validator.is_not_modified(request) {
+		return respond_not_modified(&validator);
+	}
+	let ranges = match validator.allows_ranges(request) {
+		true => request.ranges.as_slice(),
+		false => &[],
+	};
+
+	let layout = match Fragmented::read(Path::new(full_path)) {
+		Some(layout) => layout,
+		None => return Response::new(NotFound),
+	};
+
+	let file = Arc::new(file);
+	let content = layout.parts.iter()
+		.map(|part| match *part {
+			FragmentedPart::Header { begin, length } => Part::Shared { bytes: Arc::clone(&layout.headers), begin, length },
+			FragmentedPart::Media { begin, length } => Part::File { file: Arc::clone(&file), begin, length },
+		})
+		.collect();
+
+	return respond_ranges(content, "video/mp4", ranges, validator);
+}
+
+
+/// Respond with a text track of an MP4 file as WebVTT, given the index of the track
+fn respond_text_track(request: &Request, full_path: &str, index: &str) -> Response
+{
+	let index: usize = match index.parse() {
+		Ok(index) => index,
+		Err(_) => return Response::new(BadRequest),
+	};
+
+	// Get the file and its metadata or fail
+	let mut file = match OpenOptions::new().read(true).open(full_path) {
+		Ok(file) => file,
+		Err(_) => return Response::new(NotFound),
+	};
+	let metadata = match file.metadata() {
+		Ok(metadata) => metadata,
+		Err(_) => return Response::new(InternalServerError),
+	};
+
+	// Skip the response if the client has the subtitles
+	let validator = Validator::from_metadata(&metadata).with_variant(&format!("{SUBTITLES_QUERY}={index}"));
+	if validator.is_not_modified(request) {
+		return respond_not_modified(&validator);
+	}
+
+	return match read_text_track(&mut file, index) {
+		Ok(Some(webvtt)) => respond_content("text/vtt; charset=utf-8", Part::Bytes(webvtt.into_bytes()), &validator),
+		Ok(None) => Response::new(NotFound),
+		Err(_) => Response::new(InternalServerError),
+	};
+}
+
+
+/// Stream a new MP4 file made from some of the tracks of an MP4 file, or the requested ranges of it,
+/// where the variant tells it apart from the other files made from the same file
+fn respond_remux(request: &Request, full_path: &str, options: &RemuxOptions, content_type: &str, variant: &str) -> Response
+{
+	// Get the file and its metadata or fail
+	let file = match OpenOptions::new().read(true).open(full_path) {
+		Ok(file) => file,
+		Err(_) => return Response::new(NotFound),
+	};
+	let metadata = match file.metadata() {
+		Ok(metadata) => metadata,
+		Err(_) => return Response::new(InternalServerError),
+	};
+
+	// Skip the response if the client has the new file
+	let validator = Validator::from_metadata(&metadata).with_variant(variant);
+	if validator.is_not_modified(request) {
+		return respond_not_modified(&validator);
+	}
+	let ranges = match validator.allows_ranges(request) {
+		true => request.ranges.as_slice(),
+		false => &[],
+	};
+
+	let remux = match Remux::read(Path::new(full_path), options) {
+		Some(remux) => remux,
+		None => return Response::new(NotFound),
+	};
+
+	let file = Arc::new(file);
+	let mut content = vec![Part::Shared { bytes: Arc::clone(&remux.head), begin: 0, length: remux.head.len() }];
+	for &(begin, length) in &remux.media {
+		content.push(Part::File { file: Arc::clone(&file), begin, length });
+	}
+
+	return respond_ranges(content, content_type, ranges, validator);
+}
+
+
+/// Make a successful response given some non-video content
+fn respond_content(content_type: &str, content: Part, validator: &Validator) -> Response
+{
+	let mut response = Response::new(Okay);
+	response.add_header("Content-Type", content_type);
+	validator.add_headers(&mut response);
+	response.body.push(content);
+	return response;
+}
+
+
+/// Make a response without content since the client already has it
+fn respond_not_modified(validator: &Validator) -> Response
+{
+	let mut response = Response::new(NotModified);
+	validator.add_headers(&mut response);
+	return response;
+}
+
+
+/// Make a response given a file and the requested ranges, which is the whole file if there are none
+fn respond_ranges(content: Vec<Part>, content_type: &str, ranges: &[ByteRange], validator: Validator) -> Response
+{
+	// Separates the ranges in a multipart response
+	const BOUNDARY: &str = "3d6b6a416f9b5_SEESLUG_BYTERANGES";
+
+	let total_size = content.iter().map(Part::len).sum();
+
+	// Download the whole file
+	if ranges.is_empty() {
+		let mut response = Response::new(Okay);
+		response.add_header("Accept-Ranges", "bytes");
+		response.add_header("Content-Type", content_type);
+		validator.add_headers(&mut response);
+		response.body = content;
+		return response;
+	}
+
+	// Skip the ranges which are outside of the file and merge the ones which overlap
+	let ranges = resolve_ranges(ranges, total_size);
+
+	return match ranges.as_slice() {
+		// Fail if none of the ranges are satisfiable
+		[] => {
+			let mut response = Response::new(RangeNotSatisfiable);
+			response.add_header("Content-Range", &format!("bytes */{total_size}"));
+			response
+		},
+		// Stream a single range
+		&[(begin, end)] => {
+			let mut response = Response::new(PartialContent);
+			response.add_header("Content-Range", &format_content_range(begin, end, total_size));
+			response.add_header("Content-Type", content_type);
+			validator.add_headers(&mut response);
+			response.body = slice_content(&content, begin, end);
+			response
+		},
+		// Stream multiple ranges, each with its own headers
+		ranges => {
+			let mut response = Response::new(PartialContent);
+			response.add_header("Content-Type", &format!("multipart/byteranges; boundary={BOUNDARY}"));
+			validator.add_headers(&mut response);
+			for &(begin, end) in ranges {
+				let part_head = format!("\r\n--{BOUNDARY}\r\nContent-Type: {content_type}\r\nContent-Range: {}\r\n\r\n",
+					format_content_range(begin, end, total_size));
+				response.body.push(Part::Bytes(part_head.into_bytes()));
+				response.body.extend(slice_content(&content, begin, end));
+			}
+			response.body.push(Part::Bytes(format!("\r\n--{BOUNDARY}--\r\n").into_bytes()));
+			response
+		},
+	};
+}
+
+
+/// Get the parts of the content from the first byte to the last byte
+fn slice_content(content: &[Part], begin: u64, end: u64) -> Vec<Part>
+{
+	let mut result = Vec::new();
+	let mut part_begin = 0;
+	for part in content {
+		let part_end = part_begin + part.len();
+		if part_end > begin && part_begin <= end {
+			let slice_begin = begin.max(part_begin) - part_begin;
+			let slice_end = (end + 1).min(part_end) - part_begin;
+			result.push(part.slice(slice_begin, slice_end - slice_begin));
+		}
+		part_begin = part_end;
+	}
+	return result;
 }
 
 
-/// Write a response given a status code
-fn respond_status(stream: &mut TcpStream, status: Status)
+/// Format the value of a `Content-Range` header like `bytes 0-499/1234`
+fn format_content_range(begin: u64, end: u64, total_size: u64) -> String
 {
-	let _ = stream.write_all(status.to_response().as_bytes());
+	return format!("bytes {begin}-{end}/{total_size}");
 }
 
 
-/// Write a response given some non-video content
-fn respond_status_and_content(stream: &mut TcpStream, status: Status, content_type: &str, content: &[u8])
+/// Parse a time range in seconds like "90-120" or "1:30-2:00", where the end is optional like "1:30-"
+fn parse_time_range(range: &str) -> Option<(f64, Option<f64>)>
 {
-	let _ = stream.write_vectored(&[
-		IoSlice::new(b"HTTP/1.1 "),
-		IoSlice::new(status.to_str().as_bytes()),
-		IoSlice::new(b"\r\nContent-Length: "),
-		IoSlice::new(content.len().to_string().as_bytes()),
-		IoSlice::new(b"\r\nContent-Type: "),
-		IoSlice::new(content_type.as_bytes()),
-		IoSlice::new(b"\r\n\r\n"),
-		IoSlice::new(content),
-	]);
+	let (start, end) = range.split_once('-')?;
+	let start = match start {
+		"" => 0.0,
+		start => parse_time(start)?,
+	};
+	let end = match end {
+		"" => None,
+		end => Some(parse_time(end)?).filter(|&end| end > start),
+	};
+	if end.is_none() && !range.ends_with('-') {
+		return None;
+	}
+	return Some((start, end));
 }
 
 
-/// Write a response given some non-video content
-fn respond_partial_content(stream: &mut TcpStream, content_type: &str, content: &[u8], begin: usize, end: usize, total_size: usize)
+/// Parse a time in seconds like "90.5", "1:30.5", or "1:01:30.5"
+fn parse_time(time: &str) -> Option<f64>
 {
-	let _ = stream.write_vectored(&[
-		IoSlice::new(b"HTTP/1.1 206 Partial Content\r\nContent-Length: "),
-		IoSlice::new(content.len().to_string().as_bytes()),
-		IoSlice::new(b"\r\nContent-Range: bytes "),
-		IoSlice::new(begin.to_string().as_bytes()),
-		IoSlice::new(b"-"),
-		IoSlice::new(end.to_string().as_bytes()),
-		IoSlice::new(b"/"),
-		IoSlice::new(total_size.to_string().as_bytes()),
-		IoSlice::new(b"\r\nContent-Type: "),
-		IoSlice::new(content_type.as_bytes()),
-		IoSlice::new(b"\r\n\r\n"),
-		IoSlice::new(content),
-	]);
+	let mut result = 0.0;
+	for (i, part) in time.split(':').enumerate() {
+		// Only the seconds can have a fraction, and there are at most hours, minutes, and seconds
+		if i > 2 || part.is_empty() || !part.bytes().all(|byte| byte.is_ascii_digit() || byte == b'.') {
+			return None;
+		}
+		if i > 0 && result != (result as u64) as f64 {
+			return None;
+		}
+		result = result * 60.0 + part.parse::<f64>().ok()?;
+	}
+	return Some(result);
 }
 
 
@@ -320,8 +801,22 @@ fn has_parent_dir(path: &str) -> bool
 }
 
 
+/// Encode the characters which aren't allowed in a path segment of a URL, like "Star Wars.mp4" is "Star%20Wars.mp4"
+fn percent_encode(text: &str) -> String
+{
+	let mut result = String::new();
+	for byte in text.bytes() {
+		match byte {
+			b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => result.push(byte as char),
+			_ => result += &format!("%{byte:02X}"),
+		}
+	}
+	return result;
+}
+
+
 /// Escape the special HTML characters from a string
-fn escape_html(slice: &str) -> Cow<str>
+fn escape_html(slice: &str) -> Cow<'_, str>
 {
 	// Copy and escape if any escaped characters are found
 	let mut escaped_string = String::new();
@@ -374,7 +869,7 @@ fn get_last_extension(path: &str) -> &str
 
 
 /// Make the HTML page for a folder which lists videos
-fn make_html_videos<'a>(full_folder: &str, client_folder: &str, buffer: &'a mut Vec<u8>) -> &'a [u8]
+fn make_html_videos<'a>(full_folders: &[String], client_folder: &str, buffer: &'a mut Vec<u8>) -> &'a [u8]
 {
 	let folder_name = get_folder_name(client_folder);
 	let page_subtitle = match folder_name.is_empty() {
@@ -383,12 +878,20 @@ fn make_html_videos<'a>(full_folder: &str, client_folder: &str, buffer: &'a mut
 	};
 
 	let mut video_links = String::new();
-	for link_info in LinkInfo::list(full_folder) {
+	for link_info in LinkInfo::list(full_folders) {
 		let basename = escape_html(&link_info.basename);
-		let poster = match link_info.poster_extension {
-			"" => String::new(),
-			extension => format!("<img src='{}{}' loading='lazy' aria-hidden='true'>", basename, extension),
+		let has_cover = link_info.video_info.as_ref().is_some_and(|info| info.cover.is_some());
+		let poster = match (link_info.poster_extension, has_cover) {
+			("", false) => String::new(),
+			// Use the cover art in the video if there isn't a poster file
+			("", true) => format!("<img src='{}{}?{}' loading='lazy' aria-hidden='true'>", basename, link_info.video_extension, COVER_QUERY),
+			(extension, _) => format!("<img src='{}{}' loading='lazy' aria-hidden='true'>", basename, extension),
+		};
+		let title = match &link_info.metadata {
+			Some(metadata) => make_html_title(&link_info.basename, metadata),
+			None => basename.to_string(),
 		};
+		let badges = make_html_badges(&link_info);
 		let video_link = match link_info.is_folder {
 			true => format!(concat!(
 				"<div class='col-6 col-sm-4 col-md-3 col-lg-2'>",
@@ -400,18 +903,18 @@ fn make_html_videos<'a>(full_folder: &str, client_folder: &str, buffer: &'a mut
 						"<p>{}</p>",
 					"</a>",
 				"</div>",
-				), basename, poster, basename),
+				), basename, poster, title),
 			false => format!(concat!(
 				"<div class='col-6 col-sm-4 col-md-3 col-lg-2'>",
 					"<a class='video_link' href='?watch={}'>",
 						"<div class='poster' aria-hidden='true'>",
-							"{}",
+							"{}{}",
 							"<div class='overlay'></div>",
 						"</div>",
 						"<p>{}</p>",
 					"</a>",
 				"</div>",
-				), basename, poster, basename),
+				), basename, poster, badges, title),
 		};
 		video_links.push_str(&video_link);
 	}
@@ -449,9 +952,50 @@ fn make_html_videos<'a>(full_folder: &str, client_folder: &str, buffer: &'a mut
 }
 
 
-fn make_html_video<'a>(folder: &str, video_name: &str, buffer: &'a mut Vec<u8>) -> &'a [u8]
+fn make_html_video<'a>(folders: &[String], video_name: &str, buffer: &'a mut Vec<u8>) -> &'a [u8]
 {
-	let subtitles = list_subtitles(folder, video_name);
+	let mut subtitles: String = folders.iter()
+		.map(|folder| list_subtitles(folder, video_name))
+		.collect();
+	let (video_path, video_extension) = match find_video(folders, video_name) {
+		Some((path, extension)) => (Some(path), extension),
+		None => (None, VIDEO_FORMATS[0].0),
+	};
+	let metadata = Metadata::find(folders, video_name);
+
+	let video_info = match video_path {
+		Some(path) if MP4_EXTENSIONS.contains(&video_extension) => Mp4Info::read(&path),
+		_ => None,
+	};
+	if let Some(info) = &video_info {
+		subtitles += &list_embedded_subtitles(info, video_name, video_extension);
+	}
+
+	// Content type with the codecs, so the page can warn if the browser can't play them
+	let video_type = match (&video_info, video_content_type(video_extension)) {
+		(Some(info), Some(content_type)) if !info.codecs().is_empty() => format!(" data-type='{}'",
+			escape_html(&format!("{content_type}; codecs=\"{}\"", info.codecs().join(", ")))),
+		_ => String::new(),
+	};
+
+	let page_title = match &metadata {
+		Some(metadata) if !metadata.title.is_empty() => escape_html(&metadata.title),
+		_ => escape_html(video_name),
+	};
+	let info = make_html_info(video_name, metadata.as_ref(), video_info.as_deref());
+
+	// Download the audio of an MP4 file without the video
+	let has_audio = video_info.as_ref().is_some_and(|info| info.tracks.iter().any(|track| track.kind == TrackKind::Audio));
+	let download_audio = match has_audio {
+		true => format!(concat!(
+			"<button id='download_audio' title='Audio only' data-src='{}{}'>",
+				"<svg viewBox='0 0 32 32' aria-hidden='true'>",
+					"<path d='M13 22V9L23 7V20M13 22C13 23.6569 11.6569 25 10 25C8.34315 25 7 23.6569 7 22C7 20.3431 8.34315 19 10 19C11.6569 19 13 20.3431 13 22ZM23 20C23 21.6569 21.6569 23 20 23C18.3431 23 17 21.6569 17 20C17 18.3431 18.3431 17 20 17C21.6569 17 23 18.3431 23 20Z'/>",
+				"</svg>",
+			"</button>",
+			), escape_html(video_name), AUDIO_EXTENSION),
+		false => String::new(),
+	};
 
 	let video_name = escape_html(video_name);
 
@@ -468,7 +1012,8 @@ fn make_html_video<'a>(folder: &str, video_name: &str, buffer: &'a mut Vec<u8>)
 				"<link rel='manifest' href='/manifest.json' />",
 			"</head>",
 			"<body id='video_body'>",
-				"<video id='video' src='{}.mp4' autoplay controls>{}</video>",
+				"<video id='video' src='{}{}'{} autoplay controls>{}</video>",
+				"{}",
 				"<section id='controls' class='hidden'>",
 					"<div id='buttons'>",
 						"<button id='play_pause' title='Pause - Space'>",
@@ -498,6 +1043,7 @@ fn make_html_video<'a>(folder: &str, video_name: &str, buffer: &'a mut Vec<u8>)
 								"<path d='M16 8V23.9983M24 16L16 24L8 16'/>",
 							"</svg>",
 						"</button>",
+						"{}",
 						"<button id='fullscreen' title='Fullscreen - F'>",
 							"<svg id='enter_fullscreen' viewBox='0 0 32 32' aria-hidden='true'>",
 								"<path d='M13 8L8 8L8 13M24 13L24 8L19 8M19 24L24 24L24 19M8 19L8 24L13 24'/>",
@@ -526,14 +1072,150 @@ fn make_html_video<'a>(folder: &str, video_name: &str, buffer: &'a mut Vec<u8>)
 				"<script src='/video.js'></script>",
 			"</body>",
 		"</html>",
-		), video_name, video_name, subtitles).as_bytes());
+		), page_title, video_name, video_extension, video_type, subtitles, info, download_audio).as_bytes());
 
 	return buffer.as_slice();
 }
 
 
+/// Make the HTML for the title of a video link, like "Alien <span class='year'>1979</span>"
+fn make_html_title(basename: &str, metadata: &Metadata) -> String
+{
+	let mut result = match metadata.title.is_empty() {
+		true => escape_html(basename).to_string(),
+		false => escape_html(&metadata.title).to_string(),
+	};
+	if let Some(year) = metadata.year {
+		result += &format!(" <span class='year'>{year}</span>");
+	}
+	return result;
+}
+
+
+/// Make the HTML for the runtime and resolution of a video link, like "<span>1080p</span><span>1h 57m</span>"
+fn make_html_badges(link_info: &LinkInfo) -> String
+{
+	let video_info = link_info.video_info.as_deref();
+
+	let mut badges = Vec::new();
+	if let Some(video) = video_info.and_then(Mp4Info::video) {
+		match (video.width, video.height) {
+			(3840.., _) | (_, 2160..) => badges.push(String::from("4K")),
+			(1920.., _) | (_, 1080..) => badges.push(String::from("1080p")),
+			(1280.., _) | (_, 720..) => badges.push(String::from("720p")),
+			(1.., 1..) => badges.push(String::from("SD")),
+			_ => (),
+		}
+	}
+
+	if let Some(runtime) = format_runtime(get_runtime(link_info.metadata.as_ref(), video_info)) {
+		badges.push(runtime);
+	}
+
+	if badges.is_empty() {
+		return String::new();
+	}
+	let badges: String = badges.iter().map(|badge| format!("<span>{badge}</span>")).collect();
+	return format!("<div class='badges'>{badges}</div>");
+}
+
+
+/// Get the runtime in minutes from the metadata, otherwise from the length of the video
+fn get_runtime(metadata: Option<&Metadata>, video_info: Option<&Mp4Info>) -> Option<u32>
+{
+	return metadata.and_then(|metadata| metadata.runtime)
+		.or(video_info.and_then(|info| info.duration).map(|duration| (duration.as_secs_f64() / 60.0).round() as u32));
+}
+
+
+/// Format minutes like "1h 57m" or "45m", unless it's unknown
+fn format_runtime(minutes: Option<u32>) -> Option<String>
+{
+	return match minutes {
+		Some(0) | None => None,
+		Some(minutes @ ..60) => Some(format!("{minutes}m")),
+		Some(minutes) => Some(format!("{}h {}m", minutes / 60, minutes % 60)),
+	};
+}
+
+
+/// Make the HTML for the title, details, tags, and description of a video on its page, which is empty
+/// without metadata or MP4 information
+fn make_html_info(video_name: &str, metadata: Option<&Metadata>, video_info: Option<&Mp4Info>) -> String
+{
+	if metadata.is_none() && video_info.is_none() {
+		return String::new();
+	}
+	let empty = Metadata::default();
+	let metadata = metadata.unwrap_or(&empty);
+
+	let title = match metadata.title.is_empty() {
+		true => escape_html(video_name),
+		false => escape_html(&metadata.title),
+	};
+
+	// Details like "1979 · 1h 57m · 1920×800 · 8.5/10 · eng, spa"
+	let mut details = Vec::new();
+	if let Some(year) = metadata.year {
+		details.push(year.to_string());
+	}
+	if let Some(runtime) = format_runtime(get_runtime(Some(metadata), video_info)) {
+		details.push(runtime);
+	}
+	if let Some(video) = video_info.and_then(Mp4Info::video) && video.width != 0 && video.height != 0 {
+		details.push(format!("{}×{}", video.width, video.height));
+	}
+	if let Some(rating) = metadata.rating {
+		details.push(format!("{}/10", (rating * 10.0).round() / 10.0));
+	}
+	if let Some(languages) = video_info.map(Mp4Info::audio_languages) && !languages.is_empty() {
+		details.push(languages.join(", "));
+	}
+
+	let mut result = format!("<section id='info'><h1>{title}</h1>");
+	if !details.is_empty() {
+		result += &format!("<p class='details'>{}</p>", details.join(" · "));
+	}
+	if !metadata.tags.is_empty() {
+		let tags: Vec<Cow<str>> = metadata.tags.iter().map(|tag| escape_html(tag)).collect();
+		result += &format!("<p class='tags'>{}</p>", tags.join(", "));
+	}
+	if !metadata.description.is_empty() {
+		result += &format!("<p class='description'>{}</p>", escape_html(&metadata.description));
+	}
+	result += "</section>";
+
+	return result;
+}
+
+
+/// Get the path and extension of the video in the first folder and format which has it
+fn find_video(folders: &[String], video_name: &str) -> Option<(PathBuf, &'static str)>
+{
+	for folder in folders {
+		for (extension, _content_type) in VIDEO_FORMATS {
+			let path = Path::new(folder).join(format!("{video_name}{extension}"));
+			if path.is_file() {
+				return Some((path, extension));
+			}
+		}
+	}
+
+	return None;
+}
+
+
+/// Find the MP4 file in the first root folder which has it, given the path without the extension like "/Movie"
+fn find_mp4(root_folders: &[String], client_basename: &str) -> Option<String>
+{
+	return root_folders.iter()
+		.flat_map(|root_folder| MP4_EXTENSIONS.map(|extension| format!("{root_folder}{client_basename}{extension}")))
+		.find(|full_path| Path::new(full_path).is_file());
+}
+
+
 /// Given a path like "/Star Wars/Prequels" or "/" get "Prequels" or ""
-fn get_folder_name<'a>(path: &'a str) -> &'a str
+fn get_folder_name(path: &str) -> &str
 {
 	let mut begin = 0;
 	let mut end = 0;
@@ -567,8 +1249,6 @@ fn get_folder_name<'a>(path: &'a str) -> &'a str
 /// List the file names of the subtitles for the video in the folder
 fn list_subtitles(folder: &str, video_name: &str) -> String
 {
-	const VTT: &str = ".vtt";
-
 	let mut result = String::new();
 
 	let dir = match std::fs::read_dir(folder) {
@@ -592,7 +1272,11 @@ fn list_subtitles(folder: &str, video_name: &str) -> String
 
 		// Skip if the not a subtitle for this video
 		let ext = parts.extension;
-		if parts.basename != video_name || !ext.ends_with(VTT) {
+		let subtitle_extension = match SUBTITLE_EXTENSIONS.iter().find(|extension| ext.ends_with(*extension)) {
+			Some(extension) => *extension,
+			None => continue,
+		};
+		if parts.basename != video_name {
 			continue;
 		}
 
@@ -601,23 +1285,131 @@ fn list_subtitles(folder: &str, video_name: &str) -> String
 		// - NAME.default.vtt
 		// - NAME.default.LANG.vtt
 		// - NAME.LANG.vtt
+		// - Or the same with .srt, .ass, or .ssa, where LANG is a tag like "en" or "pt-BR"
 		const DEFAULT: &str = ".default.";
 		let (default, language_short) = match ext.starts_with(DEFAULT) {
-			true => match ext.len() > DEFAULT.len() + VTT.len() {
-				true => ("default", &ext[DEFAULT.len() .. (ext.len()-VTT.len())]),
+			true => match ext.len() > DEFAULT.len() + subtitle_extension.len() {
+				true => ("default", &ext[DEFAULT.len() .. (ext.len()-subtitle_extension.len())]),
 				false => ("default", ""),
 			},
-			false => match ext.len() > VTT.len() {
-				true => ("", &ext[1 .. (ext.len()-VTT.len())]),
+			false => match ext.len() > subtitle_extension.len() {
+				true => ("", &ext[1 .. (ext.len()-subtitle_extension.len())]),
 				false => ("", ""),
 			},
 		};
 
-		let language_long = language_abbrevation_to_name(language_short);
+		let language_tag = match LanguageTag::parse(language_short) {
+			Some(tag) => tag.to_canonical(),
+			None => language_short.to_string(),
+		};
+		let language_long = language_tag_to_name(language_short);
 
 		result += &format!("<track {} kind='subtitles' srclang='{}' label='{}' src='{}'>",
-			default, escape_html(language_short), language_long, escape_html(&name));
+			default, escape_html(&language_tag), escape_html(&language_long), escape_html(&name));
 	}
 
 	return result;
 }
+
+
+/// Get the HTML for the text tracks of an MP4 file which can be converted to WebVTT
+fn list_embedded_subtitles(info: &Mp4Info, video_name: &str, video_extension: &str) -> String
+{
+	let mut result = String::new();
+
+	for (i, track) in info.tracks.iter().enumerate() {
+		if track.kind != TrackKind::Text || !TEXT_FORMATS.contains(&track.codec.as_str()) {
+			continue;
+		}
+
+		let language_short = language_code_to_abbreviation(&track.language);
+		let language_long = match track.language.is_empty() {
+			true => String::from("Unknown"),
+			false => language_tag_to_name(language_short),
+		};
+
+		result += &format!("<track kind='subtitles' srclang='{}' label='{}' src='{}{}?{}={}'>",
+			escape_html(language_short), escape_html(&language_long), escape_html(video_name), video_extension, SUBTITLES_QUERY, i);
+	}
+
+	return result;
+}
+
+
+#[cfg(test)]
+mod tests
+{
+	use std::fs::File;
+
+	use super::*;
+
+
+	/// Get the content of parts in memory, with the bounds of each part
+	fn to_bytes(parts: &[Part]) -> (Vec<u8>, Vec<u64>)
+	{
+		let mut bytes = Vec::new();
+		let mut lengths = Vec::new();
+		for part in parts {
+			match part {
+				Part::Static(content) => bytes.extend(*content),
+				Part::Bytes(content) => bytes.extend(content),
+				Part::Shared { bytes: content, begin, length } => bytes.extend(&content[*begin..begin + length]),
+				Part::File { .. } => panic!("file parts aren't in memory"),
+			}
+			lengths.push(part.len());
+		}
+		return (bytes, lengths);
+	}
+
+
+	#[test]
+	fn slice_at_part_boundaries()
+	{
+		// Parts of 10, 10, and 5 bytes, where the shared one is in the middle of its bytes
+		let shared: Arc<[u8]> = (0..40).collect::<Vec<u8>>().into();
+		let content = [
+			Part::Static(b"0123456789"),
+			Part::Shared { bytes: shared, begin: 10, length: 10 },
+			Part::Bytes(b"abcde".to_vec()),
+		];
+		let all = b"0123456789\x0a\x0b\x0c\x0d\x0e\x0f\x10\x11\x12\x13abcde";
+		assert_eq!(to_bytes(&content).0, all);
+
+		for (begin, end, lengths) in [
+			(0, 9, vec![10]),
+			(9, 10, vec![1, 1]),
+			(10, 19, vec![10]),
+			(19, 20, vec![1, 1]),
+			(0, 24, vec![10, 10, 5]),
+			(24, 24, vec![1]),
+			(5, 22, vec![5, 10, 3]),
+			(12, 12, vec![1]),
+		] {
+			let (bytes, part_lengths) = to_bytes(&slice_content(&content, begin, end));
+			assert_eq!(bytes, &all[begin as usize..=end as usize], "{begin}-{end}");
+			assert_eq!(part_lengths, lengths, "{begin}-{end}");
+		}
+	}
+
+
+	#[test]
+	fn slice_of_files()
+	{
+		let path = std::env::temp_dir().join(format!("seeslug-{}-slice", std::process::id()));
+		std::fs::write(&path, b"0123456789").unwrap();
+		let file = Arc::new(File::open(&path).unwrap());
+		std::fs::remove_file(&path).unwrap();
+
+		let content = [
+			Part::Static(b"head"),
+			Part::File { file: Arc::clone(&file), begin: 2, length: 6 },
+		];
+		let parts = slice_content(&content, 3, 6);
+		assert_eq!(parts.len(), 2);
+		assert_eq!(to_bytes(&parts[..1]).0, b"d");
+		let Part::File { begin, length, .. } = parts[1] else {
+			panic!("the second part isn't a file");
+		};
+		assert_eq!((begin, length), (2, 3));
+	}
+}
@@ -0,0 +1,147 @@
+use core::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+
+/// Abbreviated day names starting with the Unix epoch, which was a Thursday
+const DAY_NAMES: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+
+/// Abbreviated month names starting with January
+const MONTH_NAMES: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+/// Seconds in a day
+const DAY_SECONDS: u64 = 24 * 60 * 60;
+
+
+/// Format a time like `Sun, 06 Nov 1994 08:49:37 GMT` for a header like `Last-Modified`
+pub fn format_http_date(time: SystemTime) -> String
+{
+	let seconds = match time.duration_since(UNIX_EPOCH) {
+		Ok(duration) => duration.as_secs(),
+		Err(_) => 0,
+	};
+
+	let days = seconds / DAY_SECONDS;
+	let day_seconds = seconds % DAY_SECONDS;
+	let (year, month, day) = civil_from_days(days);
+
+	return format!("{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+		DAY_NAMES[(days % 7) as usize], day, MONTH_NAMES[month as usize - 1], year,
+		day_seconds / 3600, day_seconds / 60 % 60, day_seconds % 60);
+}
+
+
+/// Parse a time like `Sun, 06 Nov 1994 08:49:37 GMT` from a header like `If-Modified-Since`
+pub fn parse_http_date(value: &[u8]) -> Option<SystemTime>
+{
+	let value = core::str::from_utf8(value).ok()?;
+
+	let mut parts = value.split(' ');
+	let _day_name = parts.next()?;
+	let day: u64 = parts.next()?.parse().ok()?;
+	let month_name = parts.next()?;
+	let year: u64 = parts.next()?.parse().ok()?;
+	let time = parts.next()?;
+	if parts.next()? != "GMT" || parts.next().is_some() {
+		return None;
+	}
+
+	let month = MONTH_NAMES.iter().position(|&name| name == month_name)? as u64 + 1;
+	if !(1..=31).contains(&day) || !(1970..=9999).contains(&year) {
+		return None;
+	}
+
+	let mut time_parts = time.split(':');
+	let hour: u64 = time_parts.next()?.parse().ok()?;
+	let minute: u64 = time_parts.next()?.parse().ok()?;
+	let second: u64 = time_parts.next()?.parse().ok()?;
+	if time_parts.next().is_some() || hour > 23 || minute > 59 || second > 60 {
+		return None;
+	}
+
+	let seconds = days_from_civil(year, month, day)
+		.checked_mul(DAY_SECONDS)?
+		.checked_add(hour * 3600 + minute * 60 + second)?;
+	return UNIX_EPOCH.checked_add(Duration::from_secs(seconds));
+}
+
+
+/// Get the year, month, and day from the days since the Unix epoch
+/// https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: u64) -> (u64, u64, u64)
+{
+	let days = days + 719468;
+	let era = days / 146097;
+	let day_of_era = days % 146097;
+	let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+	let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+	let shifted_month = (5 * day_of_year + 2) / 153;
+	let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+	let month = match shifted_month < 10 {
+		true => shifted_month + 3,
+		false => shifted_month - 9,
+	};
+	let year = year_of_era + era * 400 + (month <= 2) as u64;
+	return (year, month, day);
+}
+
+
+/// Get the days since the Unix epoch from the year, month, and day
+/// https://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: u64, month: u64, day: u64) -> u64
+{
+	let year = year - (month <= 2) as u64;
+	let era = year / 400;
+	let year_of_era = year % 400;
+	let shifted_month = match month > 2 {
+		true => month - 3,
+		false => month + 9,
+	};
+	let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+	let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+	return era * 146097 + day_of_era - 719468;
+}
+
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+
+	#[test]
+	fn format_and_parse()
+	{
+		let time = UNIX_EPOCH + Duration::from_secs(784111777);
+		assert_eq!(format_http_date(time), "Sun, 06 Nov 1994 08:49:37 GMT");
+		assert_eq!(parse_http_date(b"Sun, 06 Nov 1994 08:49:37 GMT"), Some(time));
+		assert_eq!(parse_http_date(b"Thu, 01 Jan 1970 00:00:00 GMT"), Some(UNIX_EPOCH));
+		assert_eq!(parse_http_date(b"Tue, 29 Feb 2000 23:59:59 GMT"), Some(UNIX_EPOCH + Duration::from_secs(951868799)));
+	}
+
+
+	#[test]
+	fn malformed_dates()
+	{
+		for value in ["", "Sun, 06 Nov 1994", "Sun, 06 Nov 1994 08:49:37", "Sun, 06 Nov 1994 08:49:37 UTC",
+			"Sun, 06 Nov 1994 08:49:37 GMT extra", "Sun, 06 Nov 1994 08:49 GMT", "Sun, 06 Nov 1994 08:49:37:00 GMT",
+			"Sun, 06 November 1994 08:49:37 GMT", "Sun, xx Nov 1994 08:49:37 GMT", "Sunday, 06-Nov-94 08:49:37 GMT",
+			"Sun Nov  6 08:49:37 1994", "Sun, 06 Nov 1994 24:00:00 GMT", "Sun, 06 Nov 1994 08:60:00 GMT",
+			"Sun, 06 Nov 1994 08:49:61 GMT", "Sun, 00 Nov 1994 08:49:37 GMT", "Sun, 32 Nov 1994 08:49:37 GMT",
+			"Sun, 06 Nov -1994 08:49:37 GMT"] {
+			assert_eq!(parse_http_date(value.as_bytes()), None, "{value}");
+		}
+		assert_eq!(parse_http_date(b"\xff\xfe"), None);
+	}
+
+
+	#[test]
+	fn out_of_range_dates()
+	{
+		assert_eq!(parse_http_date(b"Wed, 31 Dec 1969 23:59:59 GMT"), None);
+		assert_eq!(parse_http_date(b"Mon, 01 Jan 10000 00:00:00 GMT"), None);
+		assert_eq!(parse_http_date(b"Mon, 01 Jan 99999999999999 00:00:00 GMT"), None);
+		assert_eq!(parse_http_date(b"Mon, 01 Jan 99999999999999999999999 00:00:00 GMT"), None);
+		assert!(parse_http_date(b"Fri, 31 Dec 9999 23:59:59 GMT").is_some());
+	}
+}
@@ -0,0 +1,333 @@
+/// A JSON value and where it is
+pub struct Json
+{
+	pub value: JsonValue,
+	/// Line number starting at 1
+	pub line: usize,
+}
+
+
+/// The kind and content of a JSON value
+pub enum JsonValue
+{
+	Null,
+	Bool(bool),
+	Number(f64),
+	String(String),
+	Array(Vec<Json>),
+	/// Keys and values in the original order
+	Object(Vec<(String, Json)>),
+}
+
+
+/// A JSON syntax error
+pub struct JsonError
+{
+	/// Line number starting at 1
+	pub line: usize,
+	pub message: String,
+}
+
+
+/// The parse state
+struct Parser<'a>
+{
+	text: &'a [u8],
+	i: usize,
+	line: usize,
+}
+
+
+impl Json
+{
+	/// Parse the text, which must have exactly one value
+	pub fn parse(text: &str) -> Result<Json, JsonError>
+	{
+		let mut parser = Parser {
+			text: text.as_bytes(),
+			i: 0,
+			line: 1,
+		};
+
+		// Skip a byte order mark
+		if parser.text.starts_with(b"\xef\xbb\xbf") {
+			parser.i = 3;
+		}
+
+		let result = parser.parse_value()?;
+
+		parser.skip_whitespace();
+		if parser.i < parser.text.len() {
+			return Err(parser.error("Expected the end of the file"));
+		}
+
+		return Ok(result);
+	}
+
+
+	/// Get the string if it is one
+	pub fn as_str(&self) -> Option<&str>
+	{
+		return match &self.value {
+			JsonValue::String(string) => Some(string),
+			_ => None,
+		};
+	}
+
+
+	/// Get the number if it is one
+	pub fn as_f64(&self) -> Option<f64>
+	{
+		return match self.value {
+			JsonValue::Number(number) => Some(number),
+			_ => None,
+		};
+	}
+
+
+	/// Describe the kind of value for an error message
+	pub fn kind_name(&self) -> &'static str
+	{
+		return match self.value {
+			JsonValue::Null => "null",
+			JsonValue::Bool(_) => "a boolean",
+			JsonValue::Number(_) => "a number",
+			JsonValue::String(_) => "a string",
+			JsonValue::Array(_) => "an array",
+			JsonValue::Object(_) => "an object",
+		};
+	}
+}
+
+
+impl Parser<'_>
+{
+	/// Parse any value after optional whitespace
+	fn parse_value(&mut self) -> Result<Json, JsonError>
+	{
+		self.skip_whitespace();
+
+		let line = self.line;
+		let value = match self.peek() {
+			Some(b'{') => self.parse_object()?,
+			Some(b'[') => self.parse_array()?,
+			Some(b'"') => JsonValue::String(self.parse_string()?),
+			Some(b'-' | b'0'..=b'9') => JsonValue::Number(self.parse_number()?),
+			Some(b't') => self.parse_literal("true", JsonValue::Bool(true))?,
+			Some(b'f') => self.parse_literal("false", JsonValue::Bool(false))?,
+			Some(b'n') => self.parse_literal("null", JsonValue::Null)?,
+			Some(_) => return Err(self.error("Expected a value")),
+			None => return Err(self.error("Expected a value but the file ended")),
+		};
+
+		return Ok(Json { value, line });
+	}
+
+
+	/// Parse an object starting at the brace
+	fn parse_object(&mut self) -> Result<JsonValue, JsonError>
+	{
+		self.i += 1;
+		let mut entries = Vec::new();
+
+		self.skip_whitespace();
+		if self.peek() == Some(b'}') {
+			self.i += 1;
+			return Ok(JsonValue::Object(entries));
+		}
+
+		loop {
+			self.skip_whitespace();
+			if self.peek() != Some(b'"') {
+				return Err(self.error("Expected a key in quotes"));
+			}
+			let key = self.parse_string()?;
+
+			self.skip_whitespace();
+			if self.peek() != Some(b':') {
+				return Err(self.error(&format!("Expected a colon after the key \"{key}\"")));
+			}
+			self.i += 1;
+
+			let value = self.parse_value()?;
+			entries.push((key, value));
+
+			self.skip_whitespace();
+			match self.peek() {
+				Some(b',') => self.i += 1,
+				Some(b'}') => {
+					self.i += 1;
+					return Ok(JsonValue::Object(entries));
+				},
+				_ => return Err(self.error("Expected a comma or closing brace")),
+			}
+		}
+	}
+
+
+	/// Parse an array starting at the bracket
+	fn parse_array(&mut self) -> Result<JsonValue, JsonError>
+	{
+		self.i += 1;
+		let mut values = Vec::new();
+
+		self.skip_whitespace();
+		if self.peek() == Some(b']') {
+			self.i += 1;
+			return Ok(JsonValue::Array(values));
+		}
+
+		loop {
+			values.push(self.parse_value()?);
+
+			self.skip_whitespace();
+			match self.peek() {
+				Some(b',') => self.i += 1,
+				Some(b']') => {
+					self.i += 1;
+					return Ok(JsonValue::Array(values));
+				},
+				_ => return Err(self.error("Expected a comma or closing bracket")),
+			}
+		}
+	}
+
+
+	/// Parse a string starting at the quote
+	fn parse_string(&mut self) -> Result<String, JsonError>
+	{
+		self.i += 1;
+		let mut result = Vec::new();
+
+		loop {
+			let byte = match self.peek() {
+				Some(b'\n') | None => return Err(self.error("Expected a closing quote")),
+				Some(byte) => byte,
+			};
+			self.i += 1;
+
+			match byte {
+				b'"' => break,
+				b'\\' => {
+					let escaped = match self.peek() {
+						Some(escaped) => escaped,
+						None => return Err(self.error("Expected an escaped character")),
+					};
+					self.i += 1;
+					match escaped {
+						b'"' | b'\\' | b'/' => result.push(escaped),
+						b'b' => result.push(0x08),
+						b'f' => result.push(0x0c),
+						b'n' => result.push(b'\n'),
+						b'r' => result.push(b'\r'),
+						b't' => result.push(b'\t'),
+						b'u' => {
+							let c = self.parse_unicode_escape()?;
+							result.extend(c.encode_utf8(&mut [0; 4]).as_bytes());
+						},
+						_ => return Err(self.error("Expected a valid escaped character")),
+					}
+				},
+				0x00..0x20 => return Err(self.error("Expected control characters to be escaped")),
+				_ => result.push(byte),
+			}
+		}
+
+		return String::from_utf8(result).map_err(|_| self.error("Expected valid UTF-8"));
+	}
+
+
+	/// Parse the hexadecimal digits after `\u`, including a surrogate pair
+	fn parse_unicode_escape(&mut self) -> Result<char, JsonError>
+	{
+		let high = self.parse_hex_4()?;
+		if !(0xd800..0xdc00).contains(&high) {
+			return char::from_u32(high).ok_or_else(|| self.error("Expected a valid character"));
+		}
+
+		if !self.text[self.i..].starts_with(b"\\u") {
+			return Err(self.error("Expected the second half of a surrogate pair"));
+		}
+		self.i += 2;
+		let low = self.parse_hex_4()?;
+		if !(0xdc00..0xe000).contains(&low) {
+			return Err(self.error("Expected the second half of a surrogate pair"));
+		}
+
+		let code_point = 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00);
+		return char::from_u32(code_point).ok_or_else(|| self.error("Expected a valid character"));
+	}
+
+
+	/// Parse 4 hexadecimal digits
+	fn parse_hex_4(&mut self) -> Result<u32, JsonError>
+	{
+		let digits = match self.text.get(self.i..self.i+4) {
+			Some(digits) => digits,
+			None => return Err(self.error("Expected 4 hexadecimal digits")),
+		};
+		let number = core::str::from_utf8(digits).ok()
+			.and_then(|digits| u32::from_str_radix(digits, 16).ok());
+		return match number {
+			Some(number) => {
+				self.i += 4;
+				Ok(number)
+			},
+			None => Err(self.error("Expected 4 hexadecimal digits")),
+		};
+	}
+
+
+	/// Parse a number
+	fn parse_number(&mut self) -> Result<f64, JsonError>
+	{
+		let begin = self.i;
+		while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') = self.peek() {
+			self.i += 1;
+		}
+
+		let number = core::str::from_utf8(&self.text[begin..self.i]).ok()
+			.and_then(|number| number.parse().ok());
+		return number.ok_or_else(|| self.error("Expected a valid number"));
+	}
+
+
+	/// Parse a word like `true`
+	fn parse_literal(&mut self, word: &str, value: JsonValue) -> Result<JsonValue, JsonError>
+	{
+		if !self.text[self.i..].starts_with(word.as_bytes()) {
+			return Err(self.error("Expected a value"));
+		}
+		self.i += word.len();
+		return Ok(value);
+	}
+
+
+	/// Skip spaces, tabs, and line endings while counting the lines
+	fn skip_whitespace(&mut self)
+	{
+		while let Some(byte @ (b' ' | b'\t' | b'\r' | b'\n')) = self.peek() {
+			if byte == b'\n' {
+				self.line += 1;
+			}
+			self.i += 1;
+		}
+	}
+
+
+	/// Get the current byte
+	fn peek(&self) -> Option<u8>
+	{
+		return self.text.get(self.i).copied();
+	}
+
+
+	/// Make an error at the current line
+	fn error(&self, message: &str) -> JsonError
+	{
+		return JsonError {
+			line: self.line,
+			message: message.to_string(),
+		};
+	}
+}
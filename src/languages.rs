@@ -1,25 +1,1161 @@
+/// A language which is its abbreviation, terminology code, bibliographic code, English name, and native name
+type Language = (&'static str, &'static str, &'static str, &'static str, &'static str);
+
+
+/// Language if a subtitle doesn't have one
 const ASSUMED_LANGUAGE: &str = "English";
 
+/// Languages of ISO 639-2 like `("fr", "fra", "fre", "French", "français")`, which are the ISO 639-1 abbreviation
+/// if there is one, the terminology and bibliographic codes, the English name, and the native name if it's known
+/// https://www.loc.gov/standards/iso639-2/php/code_list.php
+static LANGUAGES: [Language; 486] = [
+	("aa", "aar", "",    "Afar",                                 "Qafar"),
+	("ab", "abk", "",    "Abkhazian",                            "Аԥсшәа"),
+	("ae", "ave", "",    "Avestan",                              "Avesta"),
+	("af", "afr", "",    "Afrikaans",                            "Afrikaans"),
+	("ak", "aka", "",    "Akan",                                 "Akan"),
+	("am", "amh", "",    "Amharic",                              "አማርኛ"),
+	("an", "arg", "",    "Aragonese",                            "aragonés"),
+	("ar", "ara", "",    "Arabic",                               "العربية"),
+	("as", "asm", "",    "Assamese",                             "অসমীয়া"),
+	("av", "ava", "",    "Avaric",                               "магӀарул мацӀ"),
+	("ay", "aym", "",    "Aymara",                               "Aymar aru"),
+	("az", "aze", "",    "Azerbaijani",                          "azərbaycan"),
+	("ba", "bak", "",    "Bashkir",                              "башҡорт теле"),
+	("be", "bel", "",    "Belarusian",                           "беларуская"),
+	("bg", "bul", "",    "Bulgarian",                            "български"),
+	("bh", "bih", "",    "Bihari languages",                     ""),
+	("bi", "bis", "",    "Bislama",                              "Bislama"),
+	("bm", "bam", "",    "Bambara",                              "bamanankan"),
+	("bn", "ben", "",    "Bengali",                              "বাংলা"),
+	("bo", "bod", "tib", "Tibetan",                              "བོད་སྐད་"),
+	("br", "bre", "",    "Breton",                               "brezhoneg"),
+	("bs", "bos", "",    "Bosnian",                              "bosanski"),
+	("ca", "cat", "",    "Catalan",                              "català"),
+	("ce", "che", "",    "Chechen",                              "нохчийн"),
+	("ch", "cha", "",    "Chamorro",                             "Chamoru"),
+	("co", "cos", "",    "Corsican",                             "corsu"),
+	("cr", "cre", "",    "Cree",                                 "ᓀᐦᐃᔭᐍᐏᐣ"),
+	("cs", "ces", "cze", "Czech",                                "čeština"),
+	("cu", "chu", "",    "Church Slavonic",                      "ѩзыкъ словѣньскъ"),
+	("cv", "chv", "",    "Chuvash",                              "Чӑвашла"),
+	("cy", "cym", "wel", "Welsh",                                "Cymraeg"),
+	("da", "dan", "",    "Danish",                               "dansk"),
+	("de", "deu", "ger", "German",                               "Deutsch"),
+	("dv", "div", "",    "Dhivehi",                              "ދިވެހި"),
+	("dz", "dzo", "",    "Dzongkha",                             "རྫོང་ཁ"),
+	("ee", "ewe", "",    "Ewe",                                  "Eʋegbe"),
+	("el", "ell", "gre", "Greek",                                "Ελληνικά"),
+	("en", "eng", "",    "English",                              "English"),
+	("eo", "epo", "",    "Esperanto",                            "esperanto"),
+	("es", "spa", "",    "Spanish",                              "español"),
+	("et", "est", "",    "Estonian",                             "eesti"),
+	("eu", "eus", "baq", "Basque",                               "euskara"),
+	("fa", "fas", "per", "Persian",                              "فارسی"),
+	("ff", "ful", "",    "Fulah",                                "Pulaar"),
+	("fi", "fin", "",    "Finnish",                              "suomi"),
+	("fj", "fij", "",    "Fijian",                               "vosa Vakaviti"),
+	("fo", "fao", "",    "Faroese",                              "føroyskt"),
+	("fr", "fra", "fre", "French",                               "français"),
+	("fy", "fry", "",    "Western Frisian",                      "Frysk"),
+	("ga", "gle", "",    "Irish",                                "Gaeilge"),
+	("gd", "gla", "",    "Scottish Gaelic",                      "Gàidhlig"),
+	("gl", "glg", "",    "Galician",                             "galego"),
+	("gn", "grn", "",    "Guarani",                              "Avañe'ẽ"),
+	("gu", "guj", "",    "Gujarati",                             "ગુજરાતી"),
+	("gv", "glv", "",    "Manx",                                 "Gaelg"),
+	("ha", "hau", "",    "Hausa",                                "Hausa"),
+	("he", "heb", "",    "Hebrew",                               "עברית"),
+	("hi", "hin", "",    "Hindi",                                "हिन्दी"),
+	("ho", "hmo", "",    "Hiri Motu",                            "Hiri Motu"),
+	("hr", "hrv", "",    "Croatian",                             "hrvatski"),
+	("ht", "hat", "",    "Haitian Creole",                       "Kreyòl ayisyen"),
+	("hu", "hun", "",    "Hungarian",                            "magyar"),
+	("hy", "hye", "arm", "Armenian",                             "հայերեն"),
+	("hz", "her", "",    "Herero",                               "Otjiherero"),
+	("ia", "ina", "",    "Interlingua",                          "interlingua"),
+	("id", "ind", "",    "Indonesian",                           "Bahasa Indonesia"),
+	("ie", "ile", "",    "Interlingue",                          "Interlingue"),
+	("ig", "ibo", "",    "Igbo",                                 "Asụsụ Igbo"),
+	("ii", "iii", "",    "Sichuan Yi",                           "ꆈꌠꉙ"),
+	("ik", "ipk", "",    "Inupiaq",                              "Iñupiatun"),
+	("io", "ido", "",    "Ido",                                  "Ido"),
+	("is", "isl", "ice", "Icelandic",                            "íslenska"),
+	("it", "ita", "",    "Italian",                              "italiano"),
+	("iu", "iku", "",    "Inuktitut",                            "ᐃᓄᒃᑎᑐᑦ"),
+	("ja", "jpn", "",    "Japanese",                             "日本語"),
+	("jv", "jav", "",    "Javanese",                             "basa Jawa"),
+	("ka", "kat", "geo", "Georgian",                             "ქართული"),
+	("kg", "kon", "",    "Kongo",                                "Kikongo"),
+	("ki", "kik", "",    "Kikuyu",                               "Gĩkũyũ"),
+	("kj", "kua", "",    "Kuanyama",                             "Kuanyama"),
+	("kk", "kaz", "",    "Kazakh",                               "қазақ тілі"),
+	("kl", "kal", "",    "Greenlandic",                          "kalaallisut"),
+	("km", "khm", "",    "Khmer",                                "ខ្មែរ"),
+	("kn", "kan", "",    "Kannada",                              "ಕನ್ನಡ"),
+	("ko", "kor", "",    "Korean",                               "한국어"),
+	("kr", "kau", "",    "Kanuri",                               "Kanuri"),
+	("ks", "kas", "",    "Kashmiri",                             "کٲشُر"),
+	("ku", "kur", "",    "Kurdish",                              "kurdî"),
+	("kv", "kom", "",    "Komi",                                 "коми кыв"),
+	("kw", "cor", "",    "Cornish",                              "kernewek"),
+	("ky", "kir", "",    "Kyrgyz",                               "кыргызча"),
+	("la", "lat", "",    "Latin",                                "latine"),
+	("lb", "ltz", "",    "Luxembourgish",                        "Lëtzebuergesch"),
+	("lg", "lug", "",    "Ganda",                                "Luganda"),
+	("li", "lim", "",    "Limburgish",                           "Lèmbörgs"),
+	("ln", "lin", "",    "Lingala",                              "lingála"),
+	("lo", "lao", "",    "Lao",                                  "ລາວ"),
+	("lt", "lit", "",    "Lithuanian",                           "lietuvių"),
+	("lu", "lub", "",    "Luba-Katanga",                         "Tshiluba"),
+	("lv", "lav", "",    "Latvian",                              "latviešu"),
+	("mg", "mlg", "",    "Malagasy",                             "Malagasy"),
+	("mh", "mah", "",    "Marshallese",                          "Kajin M̧ajeļ"),
+	("mi", "mri", "mao", "Maori",                                "Māori"),
+	("mk", "mkd", "mac", "Macedonian",                           "македонски"),
+	("ml", "mal", "",    "Malayalam",                            "മലയാളം"),
+	("mn", "mon", "",    "Mongolian",                            "монгол"),
+	("mr", "mar", "",    "Marathi",                              "मराठी"),
+	("ms", "msa", "may", "Malay",                                "Bahasa Melayu"),
+	("mt", "mlt", "",    "Maltese",                              "Malti"),
+	("my", "mya", "bur", "Burmese",                              "မြန်မာ"),
+	("na", "nau", "",    "Nauru",                                "Dorerin Naoero"),
+	("nb", "nob", "",    "Norwegian Bokmål",                     "norsk bokmål"),
+	("nd", "nde", "",    "North Ndebele",                        "isiNdebele"),
+	("ne", "nep", "",    "Nepali",                               "नेपाली"),
+	("ng", "ndo", "",    "Ndonga",                               "Owambo"),
+	("nl", "nld", "dut", "Dutch",                                "Nederlands"),
+	("nn", "nno", "",    "Norwegian Nynorsk",                    "nynorsk"),
+	("no", "nor", "",    "Norwegian",                            "norsk"),
+	("nr", "nbl", "",    "South Ndebele",                        "isiNdebele"),
+	("nv", "nav", "",    "Navajo",                               "Diné bizaad"),
+	("ny", "nya", "",    "Chichewa",                             "chiCheŵa"),
+	("oc", "oci", "",    "Occitan",                              "occitan"),
+	("oj", "oji", "",    "Ojibwa",                               "ᐊᓂᔑᓈᐯᒧᐎᓐ"),
+	("om", "orm", "",    "Oromo",                                "Oromoo"),
+	("or", "ori", "",    "Oriya",                                "ଓଡ଼ିଆ"),
+	("os", "oss", "",    "Ossetian",                             "ирон"),
+	("pa", "pan", "",    "Punjabi",                              "ਪੰਜਾਬੀ"),
+	("pi", "pli", "",    "Pali",                                 "पाऴि"),
+	("pl", "pol", "",    "Polish",                               "polski"),
+	("ps", "pus", "",    "Pashto",                               "پښتو"),
+	("pt", "por", "",    "Portuguese",                           "português"),
+	("qu", "que", "",    "Quechua",                              "Runa Simi"),
+	("rm", "roh", "",    "Romansh",                              "rumantsch"),
+	("rn", "run", "",    "Rundi",                                "Ikirundi"),
+	("ro", "ron", "rum", "Romanian",                             "română"),
+	("ru", "rus", "",    "Russian",                              "русский"),
+	("rw", "kin", "",    "Kinyarwanda",                          "Kinyarwanda"),
+	("sa", "san", "",    "Sanskrit",                             "संस्कृतम्"),
+	("sc", "srd", "",    "Sardinian",                            "sardu"),
+	("sd", "snd", "",    "Sindhi",                               "سنڌي"),
+	("se", "sme", "",    "Northern Sami",                        "davvisámegiella"),
+	("sg", "sag", "",    "Sango",                                "yângâ tî sängö"),
+	("si", "sin", "",    "Sinhala",                              "සිංහල"),
+	("sk", "slk", "slo", "Slovak",                               "slovenčina"),
+	("sl", "slv", "",    "Slovenian",                            "slovenščina"),
+	("sm", "smo", "",    "Samoan",                               "Gagana faʻa Sāmoa"),
+	("sn", "sna", "",    "Shona",                                "chiShona"),
+	("so", "som", "",    "Somali",                               "Soomaali"),
+	("sq", "sqi", "alb", "Albanian",                             "shqip"),
+	("sr", "srp", "",    "Serbian",                              "српски"),
+	("ss", "ssw", "",    "Swati",                                "siSwati"),
+	("st", "sot", "",    "Southern Sotho",                       "Sesotho"),
+	("su", "sun", "",    "Sundanese",                            "basa Sunda"),
+	("sv", "swe", "",    "Swedish",                              "svenska"),
+	("sw", "swa", "",    "Swahili",                              "Kiswahili"),
+	("ta", "tam", "",    "Tamil",                                "தமிழ்"),
+	("te", "tel", "",    "Telugu",                               "తెలుగు"),
+	("tg", "tgk", "",    "Tajik",                                "тоҷикӣ"),
+	("th", "tha", "",    "Thai",                                 "ไทย"),
+	("ti", "tir", "",    "Tigrinya",                             "ትግርኛ"),
+	("tk", "tuk", "",    "Turkmen",                              "türkmen dili"),
+	("tl", "tgl", "",    "Tagalog",                              "Tagalog"),
+	("tn", "tsn", "",    "Tswana",                               "Setswana"),
+	("to", "ton", "",    "Tonga",                                "lea fakatonga"),
+	("tr", "tur", "",    "Turkish",                              "Türkçe"),
+	("ts", "tso", "",    "Tsonga",                               "Xitsonga"),
+	("tt", "tat", "",    "Tatar",                                "татар"),
+	("tw", "twi", "",    "Twi",                                  "Twi"),
+	("ty", "tah", "",    "Tahitian",                             "reo Tahiti"),
+	("ug", "uig", "",    "Uyghur",                               "ئۇيغۇرچە"),
+	("uk", "ukr", "",    "Ukrainian",                            "українська"),
+	("ur", "urd", "",    "Urdu",                                 "اردو"),
+	("uz", "uzb", "",    "Uzbek",                                "o‘zbek"),
+	("ve", "ven", "",    "Venda",                                "Tshivenḓa"),
+	("vi", "vie", "",    "Vietnamese",                           "Tiếng Việt"),
+	("vo", "vol", "",    "Volapük",                              "Volapük"),
+	("wa", "wln", "",    "Walloon",                              "walon"),
+	("wo", "wol", "",    "Wolof",                                "Wolof"),
+	("xh", "xho", "",    "Xhosa",                                "isiXhosa"),
+	("yi", "yid", "",    "Yiddish",                              "ייִדיש"),
+	("yo", "yor", "",    "Yoruba",                               "Èdè Yorùbá"),
+	("za", "zha", "",    "Zhuang",                               "Vahcuengh"),
+	("zh", "zho", "chi", "Chinese",                              "中文"),
+	("zu", "zul", "",    "Zulu",                                 "isiZulu"),
+	("",   "ace", "",    "Achinese",                             ""),
+	("",   "ach", "",    "Acoli",                                ""),
+	("",   "ada", "",    "Adangme",                              ""),
+	("",   "ady", "",    "Adyghe",                               ""),
+	("",   "afa", "",    "Afro-Asiatic languages",               ""),
+	("",   "afh", "",    "Afrihili",                             ""),
+	("",   "ain", "",    "Ainu",                                 ""),
+	("",   "akk", "",    "Akkadian",                             ""),
+	("",   "ale", "",    "Aleut",                                ""),
+	("",   "alg", "",    "Algonquian languages",                 ""),
+	("",   "alt", "",    "Southern Altai",                       ""),
+	("",   "ang", "",    "Old English",                          ""),
+	("",   "anp", "",    "Angika",                               "अंगिका"),
+	("",   "apa", "",    "Apache languages",                     ""),
+	("",   "arc", "",    "Official Aramaic",                     ""),
+	("",   "arn", "",    "Mapudungun",                           ""),
+	("",   "arp", "",    "Arapaho",                              ""),
+	("",   "art", "",    "Artificial languages",                 ""),
+	("",   "arw", "",    "Arawak",                               ""),
+	("",   "ast", "",    "Asturian",                             "asturianu"),
+	("",   "ath", "",    "Athapascan languages",                 ""),
+	("",   "aus", "",    "Australian languages",                 ""),
+	("",   "awa", "",    "Awadhi",                               ""),
+	("",   "bad", "",    "Banda languages",                      ""),
+	("",   "bai", "",    "Bamileke languages",                   ""),
+	("",   "bal", "",    "Baluchi",                              ""),
+	("",   "ban", "",    "Balinese",                             ""),
+	("",   "bas", "",    "Basa",                                 ""),
+	("",   "bat", "",    "Baltic languages",                     ""),
+	("",   "bej", "",    "Beja",                                 ""),
+	("",   "bem", "",    "Bemba",                                "Ichibemba"),
+	("",   "ber", "",    "Berber languages",                     "Tamaziɣt"),
+	("",   "bho", "",    "Bhojpuri",                             "भोजपुरी"),
+	("",   "bik", "",    "Bikol",                                ""),
+	("",   "bin", "",    "Bini",                                 ""),
+	("",   "bla", "",    "Siksika",                              ""),
+	("",   "bnt", "",    "Bantu",                                ""),
+	("",   "bra", "",    "Braj",                                 ""),
+	("",   "btk", "",    "Batak languages",                      ""),
+	("",   "bua", "",    "Buriat",                               ""),
+	("",   "bug", "",    "Buginese",                             ""),
+	("",   "byn", "",    "Blin",                                 "ብሊን"),
+	("",   "cad", "",    "Caddo",                                ""),
+	("",   "cai", "",    "Central American Indian languages",    ""),
+	("",   "car", "",    "Galibi Carib",                         ""),
+	("",   "cau", "",    "Caucasian languages",                  ""),
+	("",   "ceb", "",    "Cebuano",                              ""),
+	("",   "cel", "",    "Celtic languages",                     ""),
+	("",   "chb", "",    "Chibcha",                              ""),
+	("",   "chg", "",    "Chagatai",                             ""),
+	("",   "chk", "",    "Chuukese",                             ""),
+	("",   "chm", "",    "Mari",                                 ""),
+	("",   "chn", "",    "Chinook jargon",                       ""),
+	("",   "cho", "",    "Choctaw",                              ""),
+	("",   "chp", "",    "Chipewyan",                            ""),
+	("",   "chr", "",    "Cherokee",                             "ᏣᎳᎩ"),
+	("",   "chy", "",    "Cheyenne",                             ""),
+	("",   "cmc", "",    "Chamic languages",                     ""),
+	("",   "cnr", "",    "Montenegrin",                          ""),
+	("",   "cop", "",    "Coptic",                               ""),
+	("",   "cpe", "",    "English based Creoles and pidgins",    ""),
+	("",   "cpf", "",    "French-based Creoles and pidgins",     ""),
+	("",   "cpp", "",    "Portuguese-based Creoles and pidgins", ""),
+	("",   "crh", "",    "Crimean Tatar",                        "qırımtatar tili"),
+	("",   "crp", "",    "Creoles and pidgins",                  ""),
+	("",   "csb", "",    "Kashubian",                            "kaszëbsczi jãzëk"),
+	("",   "cus", "",    "Cushitic languages",                   ""),
+	("",   "dak", "",    "Dakota",                               ""),
+	("",   "dar", "",    "Dargwa",                               ""),
+	("",   "day", "",    "Land Dayak languages",                 ""),
+	("",   "del", "",    "Delaware",                             ""),
+	("",   "den", "",    "Slave",                                ""),
+	("",   "dgr", "",    "Dogrib",                               ""),
+	("",   "din", "",    "Dinka",                                ""),
+	("",   "doi", "",    "Dogri",                                "डोगरी"),
+	("",   "dra", "",    "Dravidian languages",                  ""),
+	("",   "dsb", "",    "Lower Sorbian",                        "dolnoserbšćina"),
+	("",   "dua", "",    "Duala",                                ""),
+	("",   "dum", "",    "Middle Dutch",                         ""),
+	("",   "dyu", "",    "Dyula",                                ""),
+	("",   "efi", "",    "Efik",                                 ""),
+	("",   "egy", "",    "Egyptian",                             ""),
+	("",   "eka", "",    "Ekajuk",                               ""),
+	("",   "elx", "",    "Elamite",                              ""),
+	("",   "enm", "",    "Middle English",                       ""),
+	("",   "ewo", "",    "Ewondo",                               ""),
+	("",   "fan", "",    "Fang",                                 ""),
+	("",   "fat", "",    "Fanti",                                ""),
+	("",   "fil", "",    "Filipino",                             "Filipino"),
+	("",   "fiu", "",    "Finno-Ugrian languages",               ""),
+	("",   "fon", "",    "Fon",                                  ""),
+	("",   "frm", "",    "Middle French",                        ""),
+	("",   "fro", "",    "Old French",                           ""),
+	("",   "frr", "",    "Northern Frisian",                     ""),
+	("",   "frs", "",    "Eastern Frisian",                      ""),
+	("",   "fur", "",    "Friulian",                             "furlan"),
+	("",   "gaa", "",    "Ga",                                   ""),
+	("",   "gay", "",    "Gayo",                                 ""),
+	("",   "gba", "",    "Gbaya",                                ""),
+	("",   "gem", "",    "Germanic languages",                   ""),
+	("",   "gez", "",    "Geez",                                 "ግዕዝ"),
+	("",   "gil", "",    "Gilbertese",                           ""),
+	("",   "gmh", "",    "Middle High German",                   ""),
+	("",   "goh", "",    "Old High German",                      ""),
+	("",   "gon", "",    "Gondi",                                ""),
+	("",   "gor", "",    "Gorontalo",                            ""),
+	("",   "got", "",    "Gothic",                               ""),
+	("",   "grb", "",    "Grebo",                                ""),
+	("",   "grc", "",    "Ancient Greek",                        ""),
+	("",   "gsw", "",    "Swiss German",                         ""),
+	("",   "gwi", "",    "Gwich'in",                             ""),
+	("",   "hai", "",    "Haida",                                ""),
+	("",   "haw", "",    "Hawaiian",                             ""),
+	("",   "hil", "",    "Hiligaynon",                           ""),
+	("",   "him", "",    "Himachali languages",                  ""),
+	("",   "hit", "",    "Hittite",                              ""),
+	("",   "hmn", "",    "Hmong",                                ""),
+	("",   "hsb", "",    "Upper Sorbian",                        "hornjoserbšćina"),
+	("",   "hup", "",    "Hupa",                                 ""),
+	("",   "iba", "",    "Iban",                                 ""),
+	("",   "ijo", "",    "Ijo languages",                        ""),
+	("",   "ilo", "",    "Iloko",                                ""),
+	("",   "inc", "",    "Indic languages",                      ""),
+	("",   "ine", "",    "Indo-European languages",              ""),
+	("",   "inh", "",    "Ingush",                               ""),
+	("",   "ira", "",    "Iranian languages",                    ""),
+	("",   "iro", "",    "Iroquoian languages",                  ""),
+	("",   "jbo", "",    "Lojban",                               ""),
+	("",   "jpr", "",    "Judeo-Persian",                        ""),
+	("",   "jrb", "",    "Judeo-Arabic",                         ""),
+	("",   "kaa", "",    "Kara-Kalpak",                          ""),
+	("",   "kab", "",    "Kabyle",                               "Taqbaylit"),
+	("",   "kac", "",    "Kachin",                               ""),
+	("",   "kam", "",    "Kamba",                                ""),
+	("",   "kar", "",    "Karen languages",                      ""),
+	("",   "kaw", "",    "Kawi",                                 ""),
+	("",   "kbd", "",    "Kabardian",                            ""),
+	("",   "kha", "",    "Khasi",                                ""),
+	("",   "khi", "",    "Khoisan languages",                    ""),
+	("",   "kho", "",    "Khotanese",                            ""),
+	("",   "kmb", "",    "Kimbundu",                             ""),
+	("",   "kok", "",    "Konkani",                              "कोंकणी"),
+	("",   "kos", "",    "Kosraean",                             ""),
+	("",   "kpe", "",    "Kpelle",                               ""),
+	("",   "krc", "",    "Karachay-Balkar",                      ""),
+	("",   "krl", "",    "Karelian",                             ""),
+	("",   "kro", "",    "Kru languages",                        ""),
+	("",   "kru", "",    "Kurukh",                               ""),
+	("",   "kum", "",    "Kumyk",                                ""),
+	("",   "kut", "",    "Kutenai",                              ""),
+	("",   "lad", "",    "Ladino",                               ""),
+	("",   "lah", "",    "Lahnda",                               ""),
+	("",   "lam", "",    "Lamba",                                ""),
+	("",   "lez", "",    "Lezghian",                             ""),
+	("",   "lol", "",    "Mongo",                                ""),
+	("",   "loz", "",    "Lozi",                                 ""),
+	("",   "lua", "",    "Luba-Lulua",                           ""),
+	("",   "lui", "",    "Luiseno",                              ""),
+	("",   "lun", "",    "Lunda",                                ""),
+	("",   "luo", "",    "Luo",                                  ""),
+	("",   "lus", "",    "Lushai",                               ""),
+	("",   "mad", "",    "Madurese",                             ""),
+	("",   "mag", "",    "Magahi",                               "मगही"),
+	("",   "mai", "",    "Maithili",                             "मैथिली"),
+	("",   "mak", "",    "Makasar",                              ""),
+	("",   "man", "",    "Mandingo",                             ""),
+	("",   "map", "",    "Austronesian languages",               ""),
+	("",   "mas", "",    "Masai",                                ""),
+	("",   "mdf", "",    "Moksha",                               ""),
+	("",   "mdr", "",    "Mandar",                               ""),
+	("",   "men", "",    "Mende",                                ""),
+	("",   "mga", "",    "Middle Irish",                         ""),
+	("",   "mic", "",    "Mi'kmaq",                              ""),
+	("",   "min", "",    "Minangkabau",                          ""),
+	("",   "mis", "",    "Uncoded languages",                    ""),
+	("",   "mkh", "",    "Mon-Khmer languages",                  ""),
+	("",   "mnc", "",    "Manchu",                               ""),
+	("",   "mni", "",    "Manipuri",                             "মৈতৈলোন্"),
+	("",   "mno", "",    "Manobo languages",                     ""),
+	("",   "moh", "",    "Mohawk",                               ""),
+	("",   "mos", "",    "Mossi",                                ""),
+	("",   "mul", "",    "Multiple languages",                   ""),
+	("",   "mun", "",    "Munda languages",                      ""),
+	("",   "mus", "",    "Creek",                                ""),
+	("",   "mwl", "",    "Mirandese",                            ""),
+	("",   "mwr", "",    "Marwari",                              ""),
+	("",   "myn", "",    "Mayan languages",                      ""),
+	("",   "myv", "",    "Erzya",                                ""),
+	("",   "nah", "",    "Nahuatl languages",                    ""),
+	("",   "nai", "",    "North American Indian languages",      ""),
+	("",   "nap", "",    "Neapolitan",                           ""),
+	("",   "nds", "",    "Low German",                           "Neddersass’sch"),
+	("",   "new", "",    "Nepal Bhasa",                          ""),
+	("",   "nia", "",    "Nias",                                 ""),
+	("",   "nic", "",    "Niger-Kordofanian languages",          ""),
+	("",   "niu", "",    "Niuean",                               "ko e vagahau Niuē"),
+	("",   "nog", "",    "Nogai",                                ""),
+	("",   "non", "",    "Old Norse",                            ""),
+	("",   "nqo", "",    "N'Ko",                                 ""),
+	("",   "nso", "",    "Pedi",                                 "Sesotho sa Leboa"),
+	("",   "nub", "",    "Nubian languages",                     ""),
+	("",   "nwc", "",    "Classical Newari",                     ""),
+	("",   "nym", "",    "Nyamwezi",                             ""),
+	("",   "nyn", "",    "Nyankole",                             ""),
+	("",   "nyo", "",    "Nyoro",                                ""),
+	("",   "nzi", "",    "Nzima",                                ""),
+	("",   "osa", "",    "Osage",                                ""),
+	("",   "ota", "",    "Ottoman Turkish",                      ""),
+	("",   "oto", "",    "Otomian languages",                    ""),
+	("",   "paa", "",    "Papuan languages",                     ""),
+	("",   "pag", "",    "Pangasinan",                           ""),
+	("",   "pal", "",    "Pahlavi",                              ""),
+	("",   "pam", "",    "Pampanga",                             ""),
+	("",   "pap", "",    "Papiamento",                           "Papiamento"),
+	("",   "pau", "",    "Palauan",                              ""),
+	("",   "peo", "",    "Old Persian",                          ""),
+	("",   "phi", "",    "Philippine languages",                 ""),
+	("",   "phn", "",    "Phoenician",                           ""),
+	("",   "pon", "",    "Pohnpeian",                            ""),
+	("",   "pra", "",    "Prakrit languages",                    ""),
+	("",   "pro", "",    "Old Provençal",                        ""),
+	("",   "raj", "",    "Rajasthani",                           "राजस्थानी"),
+	("",   "rap", "",    "Rapanui",                              ""),
+	("",   "rar", "",    "Rarotongan",                           ""),
+	("",   "roa", "",    "Romance languages",                    ""),
+	("",   "rom", "",    "Romany",                               ""),
+	("",   "rup", "",    "Aromanian",                            ""),
+	("",   "sad", "",    "Sandawe",                              ""),
+	("",   "sah", "",    "Yakut",                                "саха тыла"),
+	("",   "sai", "",    "South American Indian",                ""),
+	("",   "sal", "",    "Salishan languages",                   ""),
+	("",   "sam", "",    "Samaritan Aramaic",                    ""),
+	("",   "sas", "",    "Sasak",                                ""),
+	("",   "sat", "",    "Santali",                              "ᱥᱟᱱᱛᱟᱲᱤ"),
+	("",   "scn", "",    "Sicilian",                             ""),
+	("",   "sco", "",    "Scots",                                ""),
+	("",   "sel", "",    "Selkup",                               ""),
+	("",   "sem", "",    "Semitic languages",                    ""),
+	("",   "sga", "",    "Old Irish",                            ""),
+	("",   "sgn", "",    "Sign Languages",                       ""),
+	("",   "shn", "",    "Shan",                                 "လိၵ်ႈတႆး"),
+	("",   "sid", "",    "Sidamo",                               "Sidaamu Afo"),
+	("",   "sio", "",    "Siouan languages",                     ""),
+	("",   "sit", "",    "Sino-Tibetan languages",               ""),
+	("",   "sla", "",    "Slavic languages",                     ""),
+	("",   "sma", "",    "Southern Sami",                        ""),
+	("",   "smi", "",    "Sami languages",                       ""),
+	("",   "smj", "",    "Lule Sami",                            ""),
+	("",   "smn", "",    "Inari Sami",                           ""),
+	("",   "sms", "",    "Skolt Sami",                           ""),
+	("",   "snk", "",    "Soninke",                              ""),
+	("",   "sog", "",    "Sogdian",                              ""),
+	("",   "son", "",    "Songhai languages",                    ""),
+	("",   "srn", "",    "Sranan Tongo",                         ""),
+	("",   "srr", "",    "Serer",                                ""),
+	("",   "ssa", "",    "Nilo-Saharan languages",               ""),
+	("",   "suk", "",    "Sukuma",                               ""),
+	("",   "sus", "",    "Susu",                                 ""),
+	("",   "sux", "",    "Sumerian",                             ""),
+	("",   "syc", "",    "Classical Syriac",                     ""),
+	("",   "syr", "",    "Syriac",                               "ܣܘܪܝܝܐ"),
+	("",   "tai", "",    "Tai languages",                        ""),
+	("",   "tem", "",    "Timne",                                ""),
+	("",   "ter", "",    "Tereno",                               ""),
+	("",   "tet", "",    "Tetum",                                ""),
+	("",   "tig", "",    "Tigre",                                "ትግረ"),
+	("",   "tiv", "",    "Tiv",                                  ""),
+	("",   "tkl", "",    "Tokelau",                              ""),
+	("",   "tlh", "",    "Klingon",                              ""),
+	("",   "tli", "",    "Tlingit",                              ""),
+	("",   "tmh", "",    "Tamashek",                             ""),
+	("",   "tog", "",    "Tonga",                                ""),
+	("",   "tpi", "",    "Tok Pisin",                            "Tok Pisin"),
+	("",   "tsi", "",    "Tsimshian",                            ""),
+	("",   "tum", "",    "Tumbuka",                              ""),
+	("",   "tup", "",    "Tupi languages",                       ""),
+	("",   "tut", "",    "Altaic languages",                     ""),
+	("",   "tvl", "",    "Tuvalu",                               ""),
+	("",   "tyv", "",    "Tuvinian",                             ""),
+	("",   "udm", "",    "Udmurt",                               ""),
+	("",   "uga", "",    "Ugaritic",                             ""),
+	("",   "umb", "",    "Umbundu",                              ""),
+	("",   "und", "",    "Undetermined",                         ""),
+	("",   "vai", "",    "Vai",                                  ""),
+	("",   "vot", "",    "Votic",                                ""),
+	("",   "wak", "",    "Wakashan languages",                   ""),
+	("",   "wal", "",    "Walamo",                               "ወላይታቱ"),
+	("",   "war", "",    "Waray",                                ""),
+	("",   "was", "",    "Washo",                                ""),
+	("",   "wen", "",    "Sorbian languages",                    ""),
+	("",   "xal", "",    "Kalmyk",                               ""),
+	("",   "yao", "",    "Yao",                                  ""),
+	("",   "yap", "",    "Yapese",                               ""),
+	("",   "ypk", "",    "Yupik languages",                      ""),
+	("",   "zap", "",    "Zapotec",                              ""),
+	("",   "zbl", "",    "Blissymbols",                          ""),
+	("",   "zen", "",    "Zenaga",                               ""),
+	("",   "zgh", "",    "Standard Moroccan Tamazight",          ""),
+	("",   "znd", "",    "Zande languages",                      ""),
+	("",   "zun", "",    "Zuni",                                 ""),
+	("",   "zxx", "",    "No linguistic content",                ""),
+	("",   "zza", "",    "Zaza",                                 ""),
+];
+
+/// Regions of ISO 3166-1 and UN M.49 like `("BR", "Brazil")` and `("419", "Latin America")`
+/// https://www.iso.org/iso-3166-country-codes.html
+static REGIONS: [(&str, &str); 279] = [
+	("AD",  "Andorra"),
+	("AE",  "United Arab Emirates"),
+	("AF",  "Afghanistan"),
+	("AG",  "Antigua and Barbuda"),
+	("AI",  "Anguilla"),
+	("AL",  "Albania"),
+	("AM",  "Armenia"),
+	("AO",  "Angola"),
+	("AQ",  "Antarctica"),
+	("AR",  "Argentina"),
+	("AS",  "American Samoa"),
+	("AT",  "Austria"),
+	("AU",  "Australia"),
+	("AW",  "Aruba"),
+	("AX",  "Åland Islands"),
+	("AZ",  "Azerbaijan"),
+	("BA",  "Bosnia and Herzegovina"),
+	("BB",  "Barbados"),
+	("BD",  "Bangladesh"),
+	("BE",  "Belgium"),
+	("BF",  "Burkina Faso"),
+	("BG",  "Bulgaria"),
+	("BH",  "Bahrain"),
+	("BI",  "Burundi"),
+	("BJ",  "Benin"),
+	("BL",  "Saint Barthélemy"),
+	("BM",  "Bermuda"),
+	("BN",  "Brunei"),
+	("BO",  "Bolivia"),
+	("BQ",  "Caribbean Netherlands"),
+	("BR",  "Brazil"),
+	("BS",  "Bahamas"),
+	("BT",  "Bhutan"),
+	("BV",  "Bouvet Island"),
+	("BW",  "Botswana"),
+	("BY",  "Belarus"),
+	("BZ",  "Belize"),
+	("CA",  "Canada"),
+	("CC",  "Cocos Islands"),
+	("CD",  "DR Congo"),
+	("CF",  "Central African Republic"),
+	("CG",  "Congo"),
+	("CH",  "Switzerland"),
+	("CI",  "Côte d'Ivoire"),
+	("CK",  "Cook Islands"),
+	("CL",  "Chile"),
+	("CM",  "Cameroon"),
+	("CN",  "China"),
+	("CO",  "Colombia"),
+	("CR",  "Costa Rica"),
+	("CU",  "Cuba"),
+	("CV",  "Cape Verde"),
+	("CW",  "Curaçao"),
+	("CX",  "Christmas Island"),
+	("CY",  "Cyprus"),
+	("CZ",  "Czechia"),
+	("DE",  "Germany"),
+	("DJ",  "Djibouti"),
+	("DK",  "Denmark"),
+	("DM",  "Dominica"),
+	("DO",  "Dominican Republic"),
+	("DZ",  "Algeria"),
+	("EC",  "Ecuador"),
+	("EE",  "Estonia"),
+	("EG",  "Egypt"),
+	("EH",  "Western Sahara"),
+	("ER",  "Eritrea"),
+	("ES",  "Spain"),
+	("ET",  "Ethiopia"),
+	("FI",  "Finland"),
+	("FJ",  "Fiji"),
+	("FK",  "Falkland Islands"),
+	("FM",  "Micronesia"),
+	("FO",  "Faroe Islands"),
+	("FR",  "France"),
+	("GA",  "Gabon"),
+	("GB",  "United Kingdom"),
+	("GD",  "Grenada"),
+	("GE",  "Georgia"),
+	("GF",  "French Guiana"),
+	("GG",  "Guernsey"),
+	("GH",  "Ghana"),
+	("GI",  "Gibraltar"),
+	("GL",  "Greenland"),
+	("GM",  "Gambia"),
+	("GN",  "Guinea"),
+	("GP",  "Guadeloupe"),
+	("GQ",  "Equatorial Guinea"),
+	("GR",  "Greece"),
+	("GS",  "South Georgia and the South Sandwich Islands"),
+	("GT",  "Guatemala"),
+	("GU",  "Guam"),
+	("GW",  "Guinea-Bissau"),
+	("GY",  "Guyana"),
+	("HK",  "Hong Kong"),
+	("HM",  "Heard and McDonald Islands"),
+	("HN",  "Honduras"),
+	("HR",  "Croatia"),
+	("HT",  "Haiti"),
+	("HU",  "Hungary"),
+	("ID",  "Indonesia"),
+	("IE",  "Ireland"),
+	("IL",  "Israel"),
+	("IM",  "Isle of Man"),
+	("IN",  "India"),
+	("IO",  "British Indian Ocean Territory"),
+	("IQ",  "Iraq"),
+	("IR",  "Iran"),
+	("IS",  "Iceland"),
+	("IT",  "Italy"),
+	("JE",  "Jersey"),
+	("JM",  "Jamaica"),
+	("JO",  "Jordan"),
+	("JP",  "Japan"),
+	("KE",  "Kenya"),
+	("KG",  "Kyrgyzstan"),
+	("KH",  "Cambodia"),
+	("KI",  "Kiribati"),
+	("KM",  "Comoros"),
+	("KN",  "Saint Kitts and Nevis"),
+	("KP",  "North Korea"),
+	("KR",  "South Korea"),
+	("KW",  "Kuwait"),
+	("KY",  "Cayman Islands"),
+	("KZ",  "Kazakhstan"),
+	("LA",  "Laos"),
+	("LB",  "Lebanon"),
+	("LC",  "Saint Lucia"),
+	("LI",  "Liechtenstein"),
+	("LK",  "Sri Lanka"),
+	("LR",  "Liberia"),
+	("LS",  "Lesotho"),
+	("LT",  "Lithuania"),
+	("LU",  "Luxembourg"),
+	("LV",  "Latvia"),
+	("LY",  "Libya"),
+	("MA",  "Morocco"),
+	("MC",  "Monaco"),
+	("MD",  "Moldova"),
+	("ME",  "Montenegro"),
+	("MF",  "Saint Martin"),
+	("MG",  "Madagascar"),
+	("MH",  "Marshall Islands"),
+	("MK",  "North Macedonia"),
+	("ML",  "Mali"),
+	("MM",  "Myanmar"),
+	("MN",  "Mongolia"),
+	("MO",  "Macao"),
+	("MP",  "Northern Mariana Islands"),
+	("MQ",  "Martinique"),
+	("MR",  "Mauritania"),
+	("MS",  "Montserrat"),
+	("MT",  "Malta"),
+	("MU",  "Mauritius"),
+	("MV",  "Maldives"),
+	("MW",  "Malawi"),
+	("MX",  "Mexico"),
+	("MY",  "Malaysia"),
+	("MZ",  "Mozambique"),
+	("NA",  "Namibia"),
+	("NC",  "New Caledonia"),
+	("NE",  "Niger"),
+	("NF",  "Norfolk Island"),
+	("NG",  "Nigeria"),
+	("NI",  "Nicaragua"),
+	("NL",  "Netherlands"),
+	("NO",  "Norway"),
+	("NP",  "Nepal"),
+	("NR",  "Nauru"),
+	("NU",  "Niue"),
+	("NZ",  "New Zealand"),
+	("OM",  "Oman"),
+	("PA",  "Panama"),
+	("PE",  "Peru"),
+	("PF",  "French Polynesia"),
+	("PG",  "Papua New Guinea"),
+	("PH",  "Philippines"),
+	("PK",  "Pakistan"),
+	("PL",  "Poland"),
+	("PM",  "Saint Pierre and Miquelon"),
+	("PN",  "Pitcairn"),
+	("PR",  "Puerto Rico"),
+	("PS",  "Palestine"),
+	("PT",  "Portugal"),
+	("PW",  "Palau"),
+	("PY",  "Paraguay"),
+	("QA",  "Qatar"),
+	("RE",  "Réunion"),
+	("RO",  "Romania"),
+	("RS",  "Serbia"),
+	("RU",  "Russia"),
+	("RW",  "Rwanda"),
+	("SA",  "Saudi Arabia"),
+	("SB",  "Solomon Islands"),
+	("SC",  "Seychelles"),
+	("SD",  "Sudan"),
+	("SE",  "Sweden"),
+	("SG",  "Singapore"),
+	("SH",  "Saint Helena"),
+	("SI",  "Slovenia"),
+	("SJ",  "Svalbard and Jan Mayen"),
+	("SK",  "Slovakia"),
+	("SL",  "Sierra Leone"),
+	("SM",  "San Marino"),
+	("SN",  "Senegal"),
+	("SO",  "Somalia"),
+	("SR",  "Suriname"),
+	("SS",  "South Sudan"),
+	("ST",  "Sao Tome and Principe"),
+	("SV",  "El Salvador"),
+	("SX",  "Sint Maarten"),
+	("SY",  "Syria"),
+	("SZ",  "Eswatini"),
+	("TC",  "Turks and Caicos Islands"),
+	("TD",  "Chad"),
+	("TF",  "French Southern Territories"),
+	("TG",  "Togo"),
+	("TH",  "Thailand"),
+	("TJ",  "Tajikistan"),
+	("TK",  "Tokelau"),
+	("TL",  "Timor-Leste"),
+	("TM",  "Turkmenistan"),
+	("TN",  "Tunisia"),
+	("TO",  "Tonga"),
+	("TR",  "Türkiye"),
+	("TT",  "Trinidad and Tobago"),
+	("TV",  "Tuvalu"),
+	("TW",  "Taiwan"),
+	("TZ",  "Tanzania"),
+	("UA",  "Ukraine"),
+	("UG",  "Uganda"),
+	("UM",  "U.S. Outlying Islands"),
+	("US",  "United States"),
+	("UY",  "Uruguay"),
+	("UZ",  "Uzbekistan"),
+	("VA",  "Vatican City"),
+	("VC",  "Saint Vincent and the Grenadines"),
+	("VE",  "Venezuela"),
+	("VG",  "British Virgin Islands"),
+	("VI",  "U.S. Virgin Islands"),
+	("VN",  "Vietnam"),
+	("VU",  "Vanuatu"),
+	("WF",  "Wallis and Futuna"),
+	("WS",  "Samoa"),
+	("YE",  "Yemen"),
+	("YT",  "Mayotte"),
+	("ZA",  "South Africa"),
+	("ZM",  "Zambia"),
+	("ZW",  "Zimbabwe"),
+	("001", "World"),
+	("002", "Africa"),
+	("005", "South America"),
+	("009", "Oceania"),
+	("011", "Western Africa"),
+	("013", "Central America"),
+	("014", "Eastern Africa"),
+	("015", "Northern Africa"),
+	("017", "Middle Africa"),
+	("018", "Southern Africa"),
+	("019", "Americas"),
+	("021", "Northern America"),
+	("029", "Caribbean"),
+	("030", "Eastern Asia"),
+	("034", "Southern Asia"),
+	("035", "Southeast Asia"),
+	("039", "Southern Europe"),
+	("053", "Australasia"),
+	("054", "Melanesia"),
+	("057", "Micronesian Region"),
+	("061", "Polynesia"),
+	("142", "Asia"),
+	("143", "Central Asia"),
+	("145", "Western Asia"),
+	("150", "Europe"),
+	("151", "Eastern Europe"),
+	("154", "Northern Europe"),
+	("155", "Western Europe"),
+	("202", "Sub-Saharan Africa"),
+	("419", "Latin America"),
+];
+
+/// Scripts of ISO 15924 like `("Hant", "Traditional")`
+/// https://www.unicode.org/iso15924/iso15924-codes.html
+static SCRIPTS: [(&str, &str); 180] = [
+	("Adlm", "Adlam"),
+	("Afak", "Afaka"),
+	("Aghb", "Caucasian Albanian"),
+	("Ahom", "Ahom"),
+	("Arab", "Arabic"),
+	("Aran", "Nastaliq"),
+	("Armi", "Imperial Aramaic"),
+	("Armn", "Armenian"),
+	("Avst", "Avestan"),
+	("Bali", "Balinese"),
+	("Bamu", "Bamum"),
+	("Bass", "Bassa Vah"),
+	("Batk", "Batak"),
+	("Beng", "Bengali"),
+	("Bhks", "Bhaiksuki"),
+	("Blis", "Blissymbols"),
+	("Bopo", "Bopomofo"),
+	("Brah", "Brahmi"),
+	("Brai", "Braille"),
+	("Bugi", "Buginese"),
+	("Buhd", "Buhid"),
+	("Cakm", "Chakma"),
+	("Cans", "Unified Canadian Aboriginal Syllabics"),
+	("Cari", "Carian"),
+	("Cham", "Cham"),
+	("Cher", "Cherokee"),
+	("Cirt", "Cirth"),
+	("Copt", "Coptic"),
+	("Cprt", "Cypriot"),
+	("Cyrl", "Cyrillic"),
+	("Cyrs", "Old Church Slavonic Cyrillic"),
+	("Deva", "Devanagari"),
+	("Dsrt", "Deseret"),
+	("Dupl", "Duployan shorthand"),
+	("Egyd", "Egyptian Demotic"),
+	("Egyh", "Egyptian Hieratic"),
+	("Egyp", "Egyptian Hieroglyphs"),
+	("Elba", "Elbasan"),
+	("Ethi", "Ethiopic"),
+	("Geok", "Khutsuri"),
+	("Geor", "Georgian"),
+	("Glag", "Glagolitic"),
+	("Goth", "Gothic"),
+	("Gran", "Grantha"),
+	("Grek", "Greek"),
+	("Gujr", "Gujarati"),
+	("Guru", "Gurmukhi"),
+	("Hanb", "Han with Bopomofo"),
+	("Hang", "Hangul"),
+	("Hani", "Han"),
+	("Hano", "Hanunoo"),
+	("Hans", "Simplified"),
+	("Hant", "Traditional"),
+	("Hatr", "Hatran"),
+	("Hebr", "Hebrew"),
+	("Hira", "Hiragana"),
+	("Hluw", "Anatolian Hieroglyphs"),
+	("Hmng", "Pahawh Hmong"),
+	("Hrkt", "Kana"),
+	("Hung", "Old Hungarian"),
+	("Inds", "Indus"),
+	("Ital", "Old Italic"),
+	("Jamo", "Jamo"),
+	("Java", "Javanese"),
+	("Jpan", "Japanese"),
+	("Jurc", "Jurchen"),
+	("Kali", "Kayah Li"),
+	("Kana", "Katakana"),
+	("Khar", "Kharoshthi"),
+	("Khmr", "Khmer"),
+	("Khoj", "Khojki"),
+	("Kitl", "Khitan large script"),
+	("Kits", "Khitan small script"),
+	("Knda", "Kannada"),
+	("Kore", "Korean"),
+	("Kpel", "Kpelle"),
+	("Kthi", "Kaithi"),
+	("Lana", "Tai Tham"),
+	("Laoo", "Lao"),
+	("Latf", "Fraktur Latin"),
+	("Latg", "Gaelic Latin"),
+	("Latn", "Latin"),
+	("Leke", "Leke"),
+	("Lepc", "Lepcha"),
+	("Limb", "Limbu"),
+	("Lina", "Linear A"),
+	("Linb", "Linear B"),
+	("Lisu", "Lisu"),
+	("Loma", "Loma"),
+	("Lyci", "Lycian"),
+	("Lydi", "Lydian"),
+	("Mahj", "Mahajani"),
+	("Mand", "Mandaic"),
+	("Mani", "Manichaean"),
+	("Marc", "Marchen"),
+	("Maya", "Mayan hieroglyphs"),
+	("Mend", "Mende Kikakui"),
+	("Merc", "Meroitic Cursive"),
+	("Mero", "Meroitic Hieroglyphs"),
+	("Mlym", "Malayalam"),
+	("Modi", "Modi"),
+	("Mong", "Mongolian"),
+	("Moon", "Moon"),
+	("Mroo", "Mro"),
+	("Mtei", "Meitei Mayek"),
+	("Mult", "Multani"),
+	("Mymr", "Myanmar"),
+	("Narb", "Old North Arabian"),
+	("Nbat", "Nabataean"),
+	("Newa", "Newa"),
+	("Nkgb", "Nakhi Geba"),
+	("Nkoo", "N’Ko"),
+	("Nshu", "Nüshu"),
+	("Ogam", "Ogham"),
+	("Olck", "Ol Chiki"),
+	("Orkh", "Old Turkic"),
+	("Orya", "Oriya"),
+	("Osge", "Osage"),
+	("Osma", "Osmanya"),
+	("Palm", "Palmyrene"),
+	("Pauc", "Pau Cin Hau"),
+	("Perm", "Old Permic"),
+	("Phag", "Phags-pa"),
+	("Phli", "Inscriptional Pahlavi"),
+	("Phlp", "Psalter Pahlavi"),
+	("Phlv", "Book Pahlavi"),
+	("Phnx", "Phoenician"),
+	("Piqd", "Klingon"),
+	("Plrd", "Miao"),
+	("Prti", "Inscriptional Parthian"),
+	("Rjng", "Rejang"),
+	("Roro", "Rongorongo"),
+	("Runr", "Runic"),
+	("Samr", "Samaritan"),
+	("Sara", "Sarati"),
+	("Sarb", "Old South Arabian"),
+	("Saur", "Saurashtra"),
+	("Sgnw", "SignWriting"),
+	("Shaw", "Shavian"),
+	("Shrd", "Sharada"),
+	("Sidd", "Siddham"),
+	("Sind", "Khudawadi"),
+	("Sinh", "Sinhala"),
+	("Sora", "Sora Sompeng"),
+	("Sund", "Sundanese"),
+	("Sylo", "Syloti Nagri"),
+	("Syrc", "Syriac"),
+	("Syre", "Estrangelo Syriac"),
+	("Syrj", "Western Syriac"),
+	("Syrn", "Eastern Syriac"),
+	("Tagb", "Tagbanwa"),
+	("Takr", "Takri"),
+	("Tale", "Tai Le"),
+	("Talu", "New Tai Lue"),
+	("Taml", "Tamil"),
+	("Tang", "Tangut"),
+	("Tavt", "Tai Viet"),
+	("Telu", "Telugu"),
+	("Teng", "Tengwar"),
+	("Tfng", "Tifinagh"),
+	("Tglg", "Tagalog"),
+	("Thaa", "Thaana"),
+	("Thai", "Thai"),
+	("Tibt", "Tibetan"),
+	("Tirh", "Tirhuta"),
+	("Ugar", "Ugaritic"),
+	("Vaii", "Vai"),
+	("Visp", "Visible Speech"),
+	("Wara", "Warang Citi"),
+	("Wole", "Woleai"),
+	("Xpeo", "Old Persian"),
+	("Xsux", "Cuneiform"),
+	("Yiii", "Yi"),
+	("Zinh", "Inherited"),
+	("Zmth", "Mathematical Notation"),
+	("Zsye", "Emoji"),
+	("Zsym", "Symbols"),
+	("Zxxx", "Unwritten"),
+	("Zyyy", "Common"),
+	("Zzzz", "Unknown"),
+];
+
+
+/// The subtags of a BCP 47 language tag like "zh-Hant-TW" or "pt-BR", without the extensions and private use ones
+/// https://www.rfc-editor.org/rfc/rfc5646
+pub struct LanguageTag<'a>
+{
+	/// Language like "pt" or "fil"
+	pub language: &'a str,
+	/// Script like "Hant", which is empty if there isn't one
+	pub script: &'a str,
+	/// Region like "BR" or "419", which is empty if there isn't one
+	pub region: &'a str,
+	/// Variants like "1901" in "de-CH-1901"
+	pub variants: Vec<&'a str>,
+}
+
+
+impl<'a> LanguageTag<'a>
+{
+	/// Parse a tag like "pt-BR", where the language must be lowercase like in subtitle names and the other subtags
+	/// can be in any case
+	pub fn parse(tag: &'a str) -> Option<LanguageTag<'a>>
+	{
+		let mut subtags = tag.split('-');
+		let language = subtags.next()?;
+		if !(2..=3).contains(&language.len()) || !language.bytes().all(|c| c.is_ascii_lowercase()) {
+			return None;
+		}
+
+		let mut result = LanguageTag {
+			language,
+			script: "",
+			region: "",
+			variants: Vec::new(),
+		};
+		for subtag in subtags {
+			let is_alphabetic = subtag.bytes().all(|c| c.is_ascii_alphabetic());
+			let is_numeric = subtag.bytes().all(|c| c.is_ascii_digit());
+			let is_alphanumeric = subtag.bytes().all(|c| c.is_ascii_alphanumeric());
+			let starts_with_digit = subtag.bytes().next().is_some_and(|c| c.is_ascii_digit());
+
+			// Subtags are in the order of script, region, and variants, where each one is optional
+			match subtag.len() {
+				4 if is_alphabetic && result.script.is_empty() && result.region.is_empty() && result.variants.is_empty() => {
+					result.script = subtag;
+				},
+				2 if is_alphabetic && result.region.is_empty() && result.variants.is_empty() => result.region = subtag,
+				3 if is_numeric && result.region.is_empty() && result.variants.is_empty() => result.region = subtag,
+				4 if starts_with_digit && is_alphanumeric => result.variants.push(subtag),
+				5..=8 if is_alphanumeric => result.variants.push(subtag),
+				_ => return None,
+			}
+		}
 
-/// Lengthen a language, for example `"en"` to `"English"`
-/// https://raw.githubusercontent.com/r12a/r12a.github.io/refs/heads/master/apps/subtags/languages.js
-pub fn language_abbrevation_to_name(abbreviation: &str) -> &str
-{
-	return match abbreviation {
-		""   => ASSUMED_LANGUAGE,
-		"ar" => "Arabic",
-		"bn" => "Bengali",
-		"de" => "German",
-		"en" => "English",
-		"es" => "Spanish",
-		"fr" => "French",
-		"hi" => "Hindi",
-		"id" => "Indonesian",
-		"ja" => "Japanese",
-		"pt" => "Portuguese",
-		"ru" => "Russian",
-		"ur" => "Urdu",
-		"zh" => "Chinese",
-		_ => "Other",
+		return Some(result);
+	}
+
+
+	/// Get the tag in its usual form, for example "zho-hant-tw" is "zh-Hant-TW", where the language is shortened
+	/// if it has an abbreviation
+	pub fn to_canonical(&self) -> String
+	{
+		let mut result = language_code_to_abbreviation(self.language).to_string();
+		if !self.script.is_empty() {
+			result += "-";
+			result += &self.script[..1].to_ascii_uppercase();
+			result += &self.script[1..].to_ascii_lowercase();
+		}
+		if !self.region.is_empty() {
+			result += "-";
+			result += &self.region.to_ascii_uppercase();
+		}
+		for variant in &self.variants {
+			result += "-";
+			result += &variant.to_ascii_lowercase();
+		}
+		return result;
+	}
+
+
+	/// Get the name, for example "pt-BR" is "Portuguese (Brazil) - Português", where the native name is after the
+	/// English one if it's different and the subtags which aren't known are shown as they are
+	pub fn to_name(&self) -> String
+	{
+		let language = find_language(self.language);
+
+		let mut details = Vec::new();
+		if !self.script.is_empty() {
+			let script = SCRIPTS.iter().find(|(code, _name)| code.eq_ignore_ascii_case(self.script));
+			details.push(script.map_or(self.script, |(_code, name)| name).to_string());
+		}
+		if !self.region.is_empty() {
+			let region = REGIONS.iter().find(|(code, _name)| code.eq_ignore_ascii_case(self.region));
+			details.push(region.map_or(self.region.to_ascii_uppercase(), |(_code, name)| name.to_string()));
+		}
+		details.extend(self.variants.iter().map(|variant| variant.to_ascii_lowercase()));
+
+		let mut result = language.map_or(self.language, |(_abbreviation, _code, _bibliographic_code, name, _native_name)| name).to_string();
+		if !details.is_empty() {
+			result += &format!(" ({})", details.join(", "));
+		}
+		if let Some((_abbreviation, _code, _bibliographic_code, name, native_name)) = language
+			&& !native_name.is_empty() && native_name != name {
+			result += " - ";
+			result += &capitalize(native_name);
+		}
+		return result;
+	}
+}
+
+
+/// Lengthen a language tag, for example `"en"` to `"English"` and `"pt-BR"` to `"Portuguese (Brazil) - Português"`,
+/// where a tag which can't be parsed is shown as it is
+pub fn language_tag_to_name(tag: &str) -> String
+{
+	if tag.is_empty() {
+		return ASSUMED_LANGUAGE.to_string();
+	}
+	return match LanguageTag::parse(tag) {
+		Some(tag) => tag.to_name(),
+		None => tag.to_string(),
 	};
 }
+
+
+/// Shorten an ISO 639-2 language like `"eng"` to `"en"`, where both the bibliographic and terminology codes
+/// like `"fre"` and `"fra"` are known, or get the language as is if it doesn't have an abbreviation
+pub fn language_code_to_abbreviation(code: &str) -> &str
+{
+	return match find_language(code) {
+		Some((abbreviation, ..)) if !abbreviation.is_empty() => abbreviation,
+		_ => code,
+	};
+}
+
+
+/// Whether the text is a language tag in a subtitle name, like `"en"`, `"fil"`, or `"pt-BR"`, where the language
+/// has to be known and not a group of languages like `"bad"`, so other words in names aren't mistaken for languages
+pub fn is_language_tag(text: &str) -> bool
+{
+	return LanguageTag::parse(text)
+		.and_then(|tag| find_language(tag.language))
+		.is_some_and(|(_abbreviation, _code, _bibliographic_code, name, _native_name)| !name.ends_with(" languages"));
+}
+
+
+/// Find a language by its abbreviation or either of its codes
+fn find_language(language: &str) -> Option<&'static Language>
+{
+	return LANGUAGES.iter().find(|(abbreviation, code, bibliographic_code, _name, _native_name)| {
+		!language.is_empty() && (*abbreviation == language || *code == language || *bibliographic_code == language)
+	});
+}
+
+
+/// Make the first letter uppercase, for example "français" is "Français"
+fn capitalize(text: &str) -> String
+{
+	let mut chars = text.chars();
+	return match chars.next() {
+		Some(first) => first.to_uppercase().chain(chars).collect(),
+		None => String::new(),
+	};
+}
+
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+
+	#[test]
+	fn language_tags()
+	{
+		for tag in ["en", "fil", "eng", "pt-BR", "zh-Hant-TW", "sl-rozaj"] {
+			assert!(is_language_tag(tag), "{tag}");
+		}
+		for tag in ["bad", "xyz", "abc-DE", "EN", "e", "engl", "en-", "en-Latin-US"] {
+			assert!(!is_language_tag(tag), "{tag}");
+		}
+	}
+
+
+	#[test]
+	fn language_names()
+	{
+		assert_eq!(language_tag_to_name("pt-BR"), "Portuguese (Brazil) - Português");
+		assert_eq!(language_tag_to_name("eng"), "English");
+		// Unknown languages are shown as they are written
+		assert_eq!(language_tag_to_name("xyz-Latn"), "xyz (Latin)");
+		assert_eq!(language_tag_to_name("Not a tag"), "Not a tag");
+	}
+}
@@ -1,7 +1,21 @@
 use core::cmp::Ordering;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::SystemTime;
 
+use crate::metadata::*;
+use crate::mp4::*;
 use crate::name_parts::*;
+use crate::natural_order::*;
+use crate::video_formats::*;
+
+
+/// File in a folder which lists the names of its videos and collections in order, one per line
+const ORDER_FILE: &str = ".order";
+/// First line of the order file to show the most recently modified videos and collections first
+const ORDER_BY_MODIFIED: &str = ":modified";
 
 
 /// Information for a link to a video or video collection
@@ -13,22 +27,84 @@ pub struct LinkInfo
 	pub basename: String,
 	/// ".jpg", ".jpeg", ".png", or ".webp"
 	pub poster_extension: &'static str,
+	/// Extension of a video file like ".mp4", which is empty for a folder
+	pub video_extension: &'static str,
+	/// Last time the video file or folder was modified
+	pub modified: SystemTime,
+	/// Title and other information from a file like `VIDEO.json` or `VIDEO.nfo`
+	pub metadata: Option<Metadata>,
+	/// Duration, resolution, and codecs of an MP4 video
+	pub video_info: Option<Arc<Mp4Info>>,
 }
 
 
 impl LinkInfo
 {
-	/// List all entires in the path
-	pub fn list(folder: &str) -> Vec<LinkInfo>
+	/// List all entires in the folders, where the first folder is used if the names are the same
+	pub fn list(folders: &[String]) -> Vec<LinkInfo>
+	{
+		let mut result = Vec::new();
+		let mut poster_extensions = HashMap::new();
+		let mut metadata_names = HashSet::new();
+
+		for folder in folders {
+			LinkInfo::list_folder(folder, &mut result, &mut poster_extensions, &mut metadata_names);
+		}
+
+		// Skip the videos and folders which are also in a previous folder
+		let mut names = HashSet::new();
+		result.retain(|link_info| names.insert((link_info.is_folder, link_info.basename.clone())));
+
+		LinkInfo::sort(folders, &mut result);
+
+		for link_info in &mut result {
+			match poster_extensions.get(&link_info.basename) {
+				Some(ext) => link_info.poster_extension = ext,
+				None => (),
+			}
+			if metadata_names.contains(&link_info.basename) {
+				link_info.metadata = Metadata::find(folders, &link_info.basename);
+			}
+		}
+
+		return result;
+	}
+
+
+	/// Sort naturally, unless the order file of the first folder which has one lists the names or
+	/// asks for the most recently modified first, where the unlisted names are after the listed ones
+	fn sort(folders: &[String], result: &mut [LinkInfo])
+	{
+		let order = folders.iter()
+			.find_map(|folder| std::fs::read_to_string(Path::new(folder).join(ORDER_FILE)).ok())
+			.unwrap_or_default();
+		let mut lines = order.lines()
+			.map(str::trim)
+			.filter(|line| !line.is_empty())
+			.peekable();
+
+		if lines.peek() == Some(&ORDER_BY_MODIFIED) {
+			result.sort_by(|a, b| b.modified.cmp(&a.modified).then_with(|| a.cmp(b)));
+			return;
+		}
+
+		let positions: HashMap<&str, usize> = lines.enumerate()
+			.map(|(i, line)| (line, i))
+			.collect();
+		let position = |link_info: &LinkInfo| positions.get(link_info.basename.as_str()).copied().unwrap_or(usize::MAX);
+		result.sort_by(|a, b| position(a).cmp(&position(b)).then_with(|| a.cmp(b)));
+	}
+
+
+	/// List all entries in the path
+	fn list_folder(folder: &str, result: &mut Vec<LinkInfo>, poster_extensions: &mut HashMap<String, &'static str>,
+		metadata_names: &mut HashSet<String>)
 	{
 		let dir = match std::fs::read_dir(folder) {
 			Ok(dir) => dir,
-			Err(_) => return Vec::new(),
+			Err(_) => return,
 		};
 
-		let mut result = Vec::new();
-		let mut poster_extensions = HashMap::new();
-
 		for entry in dir {
 			let entry = match entry {
 				Ok(entry) => entry,
@@ -40,53 +116,68 @@ impl LinkInfo
 				Err(_) => continue,
 			};
 
+			// Follow links to get the folder or file
+			let metadata = match std::fs::metadata(entry.path()) {
+				Ok(metadata) => metadata,
+				Err(_) => continue,
+			};
+			let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+
+			// Use the whole name of a folder, which can have dots like "Mr. Robot"
+			if metadata.is_dir() {
+				result.push(LinkInfo {
+					is_folder: true,
+					basename: name,
+					poster_extension: "",
+					video_extension: "",
+					modified,
+					metadata: None,
+					video_info: None,
+				});
+				continue;
+			}
+
 			let parts = NameParts::new(&name);
 			match parts.extension {
-				"" => {
-					result.push(LinkInfo {
-						is_folder: true,
-						basename: name,
-						poster_extension: "",
-					});
-				},
-				".mp4" => {
-					name.truncate(parts.basename.len());
-					result.push(LinkInfo {
-						is_folder: false,
-						basename: name,
-						poster_extension: "",
-					});
-				},
 				".jpg" => {
 					name.truncate(parts.basename.len());
-					poster_extensions.insert(name, ".jpg");
+					poster_extensions.entry(name).or_insert(".jpg");
 				},
 				".jpeg" => {
 					name.truncate(parts.basename.len());
-					poster_extensions.insert(name, ".jpeg");
+					poster_extensions.entry(name).or_insert(".jpeg");
 				},
 				".png" => {
 					name.truncate(parts.basename.len());
-					poster_extensions.insert(name, ".png");
+					poster_extensions.entry(name).or_insert(".png");
 				},
 				".webp" => {
 					name.truncate(parts.basename.len());
-					poster_extensions.insert(name, ".webp");
+					poster_extensions.entry(name).or_insert(".webp");
+				},
+				extension if METADATA_EXTENSIONS.contains(&extension) => {
+					name.truncate(parts.basename.len());
+					metadata_names.insert(name);
+				},
+				extension if let Some((video_extension, _content_type)) = find_video_format(extension) => {
+					let video_info = match MP4_EXTENSIONS.contains(&video_extension) {
+						true => Mp4Info::read(&entry.path()),
+						false => None,
+					};
+					name.truncate(parts.basename.len());
+					result.push(LinkInfo {
+						is_folder: false,
+						basename: name,
+						poster_extension: "",
+						video_extension,
+						modified,
+						metadata: None,
+						video_info,
+					});
 				},
 				_ => (),
 			}
 		}
-
-		result.sort();
-
-		for link_info in &mut result {
-			match poster_extensions.get(&link_info.basename) {
-				Some(ext) => link_info.poster_extension = ext,
-				None => (),
-			}
-		}
-
-		return result;
 	}
 }
 
@@ -108,7 +199,7 @@ impl PartialOrd for LinkInfo
 {
 	fn partial_cmp(&self, other: &LinkInfo) -> Option<Ordering>
 	{
-		return self.basename.partial_cmp(&other.basename);
+		return Some(self.cmp(other));
 	}
 }
 
@@ -117,6 +208,6 @@ impl Ord for LinkInfo
 {
 	fn cmp(&self, other: &LinkInfo) -> Ordering
 	{
-		return self.basename.cmp(&other.basename);
+		return compare_naturally(&self.basename, &other.basename);
 	}
 }
@@ -0,0 +1,127 @@
+use std::net::SocketAddr;
+use std::net::TcpListener;
+
+
+/// Start listening to an address, where an IPv6 address also accepts IPv4 clients unless it's IPv6 only
+#[cfg(target_os = "linux")]
+pub fn listen(address: SocketAddr, ipv6_only: bool) -> std::io::Result<TcpListener>
+{
+	use core::ffi::c_int;
+	use core::ffi::c_void;
+	use std::os::fd::AsRawFd;
+	use std::os::fd::FromRawFd;
+	use std::os::fd::OwnedFd;
+
+	unsafe extern "C"
+	{
+		// https://man7.org/linux/man-pages/man2/socket.2.html
+		fn socket(domain: c_int, kind: c_int, protocol: c_int) -> c_int;
+		// https://man7.org/linux/man-pages/man2/setsockopt.2.html
+		fn setsockopt(sockfd: c_int, level: c_int, optname: c_int, optval: *const c_void, optlen: u32) -> c_int;
+		// https://man7.org/linux/man-pages/man2/bind.2.html
+		fn bind(sockfd: c_int, addr: *const c_void, addrlen: u32) -> c_int;
+		// https://man7.org/linux/man-pages/man2/listen.2.html
+		fn listen(sockfd: c_int, backlog: c_int) -> c_int;
+	}
+
+	/// IPv4 (`AF_INET`)
+	const IPV4: u16 = 2;
+	/// IPv6 (`AF_INET6`)
+	const IPV6: u16 = 10;
+	/// TCP (`SOCK_STREAM`)
+	const STREAM: c_int = 1;
+	/// Close the socket when the process executes another program (`SOCK_CLOEXEC`)
+	const CLOSE_ON_EXEC: c_int = 0x80000;
+	/// Socket level (`SOL_SOCKET`)
+	const SOCKET_LEVEL: c_int = 1;
+	/// Allow listening again while old connections are closing (`SO_REUSEADDR`)
+	const REUSE_ADDRESS: c_int = 2;
+	/// IPv6 level (`IPPROTO_IPV6`)
+	const IPV6_LEVEL: c_int = 41;
+	/// Only accept IPv6 clients (`IPV6_V6ONLY`)
+	const IPV6_ONLY: c_int = 26;
+	/// Maximum amount of connections waiting to be accepted
+	const BACKLOG: c_int = 128;
+
+	/// IPv4 socket address (`sockaddr_in`)
+	#[repr(C)]
+	struct Ipv4Address
+	{
+		family: u16,
+		port: [u8; 2],
+		address: [u8; 4],
+		zero: [u8; 8],
+	}
+
+	/// IPv6 socket address (`sockaddr_in6`)
+	#[repr(C)]
+	struct Ipv6Address
+	{
+		family: u16,
+		port: [u8; 2],
+		flow_info: u32,
+		address: [u8; 16],
+		scope_id: u32,
+	}
+
+	let family = match address {
+		SocketAddr::V4(_) => IPV4,
+		SocketAddr::V6(_) => IPV6,
+	};
+
+	let fd = unsafe { socket(family as c_int, STREAM | CLOSE_ON_EXEC, 0) };
+	if fd < 0 {
+		return Err(std::io::Error::last_os_error());
+	}
+	let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+	let set_option = |level: c_int, name: c_int, value: c_int| -> std::io::Result<()> {
+		let value_pointer = &value as *const c_int as *const c_void;
+		if unsafe { setsockopt(fd.as_raw_fd(), level, name, value_pointer, size_of::<c_int>() as u32) } < 0 {
+			return Err(std::io::Error::last_os_error());
+		}
+		return Ok(());
+	};
+
+	set_option(SOCKET_LEVEL, REUSE_ADDRESS, 1)?;
+
+	let result = match address {
+		SocketAddr::V4(address) => {
+			let address = Ipv4Address {
+				family,
+				port: address.port().to_be_bytes(),
+				address: address.ip().octets(),
+				zero: [0; 8],
+			};
+			unsafe { bind(fd.as_raw_fd(), &address as *const Ipv4Address as *const c_void, size_of::<Ipv4Address>() as u32) }
+		},
+		SocketAddr::V6(address) => {
+			set_option(IPV6_LEVEL, IPV6_ONLY, ipv6_only as c_int)?;
+			let address = Ipv6Address {
+				family,
+				port: address.port().to_be_bytes(),
+				flow_info: address.flowinfo().to_be(),
+				address: address.ip().octets(),
+				scope_id: address.scope_id(),
+			};
+			unsafe { bind(fd.as_raw_fd(), &address as *const Ipv6Address as *const c_void, size_of::<Ipv6Address>() as u32) }
+		},
+	};
+	if result < 0 {
+		return Err(std::io::Error::last_os_error());
+	}
+
+	if unsafe { listen(fd.as_raw_fd(), BACKLOG) } < 0 {
+		return Err(std::io::Error::last_os_error());
+	}
+
+	return Ok(TcpListener::from(fd));
+}
+
+
+/// Start listening to an address, where the system decides whether IPv6 addresses also accept IPv4 clients
+#[cfg(not(target_os = "linux"))]
+pub fn listen(address: SocketAddr, _ipv6_only: bool) -> std::io::Result<TcpListener>
+{
+	return TcpListener::bind(address);
+}
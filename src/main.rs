@@ -1,23 +1,54 @@
+#![allow(clippy::needless_return)]
+#![allow(clippy::single_match)]
+
 mod arguments;
+mod ass;
+mod charset;
 mod http;
+mod http_date;
+mod json;
 mod languages;
 mod link_info;
+mod listener;
+mod metadata;
+mod mp4;
+mod mp4_remux;
+mod mp4_text;
 mod name_parts;
+mod natural_order;
 mod print;
+#[cfg(target_os = "linux")]
+mod reactor;
 mod request;
+mod response;
+mod server;
+mod shutdown;
+mod srt;
 mod status;
 mod thread_pool;
+mod validator;
+mod video_formats;
+mod webvtt;
 
 
-use core::ffi::c_int;
 use core::mem::transmute;
+use core::net::IpAddr;
 use core::net::Ipv4Addr;
+use core::net::Ipv6Addr;
+use core::net::SocketAddr;
+use core::time::Duration;
 use std::net::TcpListener;
+use std::net::TcpStream;
 
 use crate::arguments::*;
 use crate::http::*;
+use crate::listener::*;
+use crate::server::*;
 use crate::thread_pool::*;
 use crate::print::*;
+use crate::shutdown::*;
+#[cfg(target_os = "linux")]
+use crate::reactor::*;
 
 
 /// Number to send to the shell
@@ -33,14 +64,9 @@ use ExitCode::*;
 /// Run a multi-threaded video server
 fn main()
 {
-	const SIGINT: core::ffi::c_int = 2;
-	unsafe extern "C"
-	{
-		fn signal(signum: core::ffi::c_int, handler: usize) -> usize;
-	}
-
-	// Handle signals without displaying error messages
-	unsafe { signal(SIGINT, handle_interrupt as usize); }
+	// Handle signals by finishing the responses without displaying error messages
+	handle_shutdown_signals();
+	handle_inspect_signal();
 
 	// Get the normal program mode from the arguments or exit early
 	let config = match Mode::new() {
@@ -53,37 +79,116 @@ fn main()
 		Mode::Version => return print_version(),
 	};
 
-	// Create a network listener or fail
-	let mut listener = match TcpListener::bind((Ipv4Addr::UNSPECIFIED, config.port)) {
-		Ok(listener) => listener,
-		Err(error) => {
-			eprint(&format!("Failed to start listening to port {} - {error}\nHint: try another number with the --port argument\n", config.port));
-			std::process::exit(FailedToListen as i32);
+	// Create the network listeners or fail, where an IPv6 listener is only for IPv6
+	// if there's also an IPv4 listener for the same port
+	let mut listeners = Vec::with_capacity(config.listen.len());
+	for &address in &config.listen {
+		let ipv6_only = address.is_ipv6() && config.listen.iter()
+			.any(|other| other.is_ipv4() && other.port() == address.port());
+		match listen(address, ipv6_only) {
+			Ok(listener) => listeners.push(listener),
+			Err(error) => {
+				eprint(&format!("Failed to start listening to {address} - {error}\nHint: try another port with the --listen argument\n"));
+				std::process::exit(FailedToListen as i32);
+			}
 		}
-	};
-	#[cfg(target_os = "windows")]
-	fix_listener(&mut listener);
+		#[cfg(target_os = "windows")]
+		fix_listener(listeners.last().unwrap());
+	}
 
 	// Treat the data created in main as static
-	let root_folder = unsafe { transmute::<&str, &'static str>(&config.folder) };
-
-	// Listen to the clients and have the thread pool handle them
-	let pool = ThreadPool::new();
-	for stream in listener.incoming() {
-		match stream {
-			Ok(stream) => pool.execute(|| handle_request(root_folder, stream)),
-			Err(_) => (),
-		}
+	let config = unsafe { transmute::<&Config, &'static Config>(&config) };
+	let server = Server::new(config.max_connections);
+	let server = unsafe { transmute::<&Server, &'static Server>(&server) };
+
+	let pool = ThreadPool::new(config.threads);
+
+	// Handle the clients with an event loop and have the thread pool make the responses
+	#[cfg(target_os = "linux")]
+	let listeners = match Reactor::new(config, server, &pool) {
+		Ok(mut reactor) => {
+			if let Err(error) = reactor.run(listeners) {
+				eprint(&format!("Failed to wait for network events - {error}\n"));
+				std::process::exit(FailedToListen as i32);
+			}
+			Vec::new()
+		},
+		Err(error) => {
+			eprint(&format!("Warning: Failed to start the event loop, so each connection uses a thread - {error}\n"));
+			listeners
+		},
+	};
+
+	if !listeners.is_empty() {
+		serve_with_threads(listeners, config, server, &pool);
 	}
+
+	// Wait for the responses to finish unless the grace timer exits first
+	drop(pool);
+	std::process::exit(Success as i32);
+}
+
+
+/// Accept the clients with a thread per listener and have the thread pool handle them until the server shuts down
+fn serve_with_threads(listeners: Vec<TcpListener>, config: &'static Config, server: &'static Server, pool: &ThreadPool)
+{
+	/// Time between checking whether a signal asked to shut down or to print the connections
+	const SHUTDOWN_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+	// Get the addresses to connect to when shutting down before the threads take the listeners
+	let addresses: Vec<SocketAddr> = listeners.iter()
+		.filter_map(|listener| listener.local_addr().ok())
+		.collect();
+
+	std::thread::scope(|scope| {
+		for listener in listeners {
+			scope.spawn(move || {
+				for stream in listener.incoming() {
+					if is_shutting_down() {
+						return;
+					}
+					let stream = match stream {
+						Ok(stream) => stream,
+						Err(_) => continue,
+					};
+					let address = match stream.peer_addr() {
+						Ok(address) => address,
+						Err(_) => continue,
+					};
+					match server.add(address) {
+						Some(handle) => pool.execute(|| handle_connection(config, handle, stream)),
+						None => reject_connection(stream),
+					}
+				}
+			});
+		}
+
+		while !is_shutting_down() {
+			std::thread::sleep(SHUTDOWN_CHECK_INTERVAL);
+			server.print_if_requested();
+		}
+		start_grace_timer(config.shutdown_timeout);
+
+		// Wake the threads waiting for connections by connecting to each listener, so they close them
+		for mut address in addresses {
+			match address {
+				SocketAddr::V4(_) if address.ip().is_unspecified() => address.set_ip(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+				SocketAddr::V6(_) if address.ip().is_unspecified() => address.set_ip(IpAddr::V6(Ipv6Addr::LOCALHOST)),
+				_ => (),
+			}
+			let _ = TcpStream::connect(address);
+		}
+	});
 }
 
 
 /// Prevent the Windows firewall from resetting incoming connections
 /// (solution found by randomly changing different socket options)
 #[cfg(target_os = "windows")]
-fn fix_listener(listener: &mut TcpListener)
+fn fix_listener(listener: &TcpListener)
 {
 	use core::ffi::c_char;
+	use core::ffi::c_int;
 	use std::os::windows::io::AsRawSocket;
 	use std::os::windows::raw::SOCKET;
 
@@ -121,8 +226,16 @@ fn print_help()
 		"https://github.com/RobbyCBennett/SeeSlug\n",
 		"\n",
 		"Config arguments:\n",
-		"    --folder  (default: \".\")\n",
-		"    --port    (default: 80)\n",
+		"    --config            (JSON file with the settings below, which the arguments override)\n",
+		"    --folder            (default: \".\", can be repeated)\n",
+		"    --listen            (default: the address and port, can be repeated, like 0.0.0.0:80, [::1]:80, or *:80)\n",
+		"    --port              (default: 80)\n",
+		"    --address           (default: 0.0.0.0)\n",
+		"    --threads           (default: 0, which is the amount of processors)\n",
+		"    --idle-timeout      (default: 15 seconds)\n",
+		"    --shutdown-timeout  (default: 30 seconds)\n",
+		"    --max-connections   (default: 0, which is unlimited)\n",
+		"    --log               (default: errors, or none or requests)\n",
 		"\n",
 		"Other arguments:\n",
 		"    --help or -h\n",
@@ -136,10 +249,3 @@ fn print_version()
 {
 	print(concat!(env!("CARGO_PKG_NAME"), " ", env!("CARGO_PKG_VERSION"), "\n"));
 }
-
-
-/// Immediately exit
-extern "C" fn handle_interrupt(_signal: c_int)
-{
-	std::process::exit(Success as i32);
-}
@@ -0,0 +1,253 @@
+use std::path::Path;
+
+use crate::json::*;
+
+
+/// Extension of a metadata file with a JSON object
+const JSON_EXTENSION: &str = ".json";
+/// Extension of a Kodi metadata file with XML
+const NFO_EXTENSION: &str = ".nfo";
+
+/// Extensions of metadata files, where the first one is used if a video has both
+pub const METADATA_EXTENSIONS: [&str; 2] = [JSON_EXTENSION, NFO_EXTENSION];
+
+
+/// Information about a video or collection from a file next to it like `VIDEO.json` or `VIDEO.nfo`
+#[derive(Default)]
+pub struct Metadata
+{
+	/// Name to display instead of the basename, which is empty if there isn't one
+	pub title: String,
+	/// Year it was released
+	pub year: Option<u32>,
+	/// Synopsis, which is empty if there isn't one
+	pub description: String,
+	/// Length in minutes
+	pub runtime: Option<u32>,
+	/// Rating out of 10
+	pub rating: Option<f64>,
+	/// Genres and other tags
+	pub tags: Vec<String>,
+}
+
+
+impl Metadata
+{
+	/// Read the metadata of a video or collection from the first folder which has a metadata file for it,
+	/// where an invalid file is skipped
+	pub fn find(folders: &[String], basename: &str) -> Option<Metadata>
+	{
+		for folder in folders {
+			for extension in METADATA_EXTENSIONS {
+				let text = match std::fs::read_to_string(Path::new(folder).join(format!("{basename}{extension}"))) {
+					Ok(text) => text,
+					Err(_) => continue,
+				};
+				let metadata = match extension {
+					JSON_EXTENSION => Metadata::from_json(&text),
+					_ => Some(Metadata::from_nfo(&text)),
+				};
+				if metadata.is_some() {
+					return metadata;
+				}
+			}
+		}
+
+		return None;
+	}
+
+
+	/// Parse a JSON object like `{"title": "Alien", "year": 1979, "tags": ["Horror"]}`
+	fn from_json(text: &str) -> Option<Metadata>
+	{
+		let json = Json::parse(text).ok()?;
+		let entries = match &json.value {
+			JsonValue::Object(entries) => entries,
+			_ => return None,
+		};
+
+		let mut metadata = Metadata::default();
+		for (key, value) in entries {
+			match key.as_str() {
+				"title" => metadata.title = value.as_str().unwrap_or_default().trim().to_string(),
+				"year" => metadata.year = value.as_f64().and_then(to_whole_number),
+				"description" => metadata.description = value.as_str().unwrap_or_default().trim().to_string(),
+				"runtime" => metadata.runtime = value.as_f64().and_then(to_whole_number),
+				"rating" => metadata.rating = value.as_f64().filter(|rating| (0.0..=10.0).contains(rating)),
+				"tags" => match &value.value {
+					JsonValue::Array(tags) => metadata.tags = tags.iter()
+						.filter_map(Json::as_str)
+						.map(|tag| tag.trim().to_string())
+						.filter(|tag| !tag.is_empty())
+						.collect(),
+					_ => (),
+				},
+				_ => (),
+			}
+		}
+
+		return Some(metadata);
+	}
+
+
+	/// Parse the elements of a Kodi movie or episode like `<movie><title>Alien</title><year>1979</year></movie>`
+	/// https://kodi.wiki/view/NFO_files/Movies
+	fn from_nfo(text: &str) -> Metadata
+	{
+		let first = |name: &str| find_xml_elements(text, name).into_iter().next().unwrap_or_default();
+
+		// Get the year from the release date like "1979-05-25" if there's no year
+		let year = match first("year") {
+			year if !year.is_empty() => year,
+			_ => first("premiered").chars().take(4).collect(),
+		};
+
+		// Get the rating from the newer format like `<ratings><rating><value>8.5</value></rating></ratings>`
+		// before the older format like `<rating>8.5</rating>`
+		let rating = match first("value").parse::<f64>() {
+			Ok(rating) => Some(rating),
+			Err(_) => first("rating").parse::<f64>().ok(),
+		};
+
+		let description = match first("plot") {
+			plot if !plot.is_empty() => plot,
+			_ => first("outline"),
+		};
+
+		let mut tags = find_xml_elements(text, "genre");
+		tags.extend(find_xml_elements(text, "tag"));
+		tags.retain(|tag| !tag.is_empty());
+
+		return Metadata {
+			title: first("title"),
+			year: year.parse().ok(),
+			description,
+			runtime: first("runtime").parse().ok(),
+			rating: rating.filter(|rating| (0.0..=10.0).contains(rating)),
+			tags,
+		};
+	}
+}
+
+
+/// Get a number if it's whole and not negative
+fn to_whole_number(number: f64) -> Option<u32>
+{
+	return match number.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(&number) {
+		true => Some(number as u32),
+		false => None,
+	};
+}
+
+
+/// Get the text of each element with the name, like "Drama" in `<genre>Drama</genre>`, where the tags of
+/// the elements inside are removed
+fn find_xml_elements(xml: &str, name: &str) -> Vec<String>
+{
+	let open = format!("<{name}");
+	let close = format!("</{name}>");
+
+	let mut result = Vec::new();
+	let mut rest = xml;
+
+	while let Some(start) = rest.find(&open) {
+		rest = &rest[start + open.len()..];
+
+		// Skip other elements starting with the name, like `<titles>` instead of `<title>`
+		if !rest.starts_with(['>', '/', ' ', '\t', '\r', '\n']) {
+			continue;
+		}
+
+		// Skip the attributes and empty elements like `<tag/>`
+		let content_start = match rest.find('>') {
+			Some(i) => i + 1,
+			None => break,
+		};
+		if rest[..content_start].ends_with("/>") {
+			rest = &rest[content_start..];
+			continue;
+		}
+		rest = &rest[content_start..];
+
+		let content_end = match rest.find(&close) {
+			Some(i) => i,
+			None => break,
+		};
+		result.push(decode_xml_text(&rest[..content_end]));
+		rest = &rest[content_end + close.len()..];
+	}
+
+	return result;
+}
+
+
+/// Get the text without tags, comments, and entities like `&amp;`, where `<![CDATA[...]]>` is kept as is
+fn decode_xml_text(text: &str) -> String
+{
+	const CDATA_START: &str = "<![CDATA[";
+	const CDATA_END: &str = "]]>";
+	const COMMENT_START: &str = "<!--";
+	const COMMENT_END: &str = "-->";
+	/// Longest entity between '&' and ';' which is decoded, like "#x1F600"
+	const MAX_ENTITY_SIZE: usize = 8;
+
+	let mut result = String::new();
+	let mut rest = text;
+
+	while let Some(i) = rest.find(['<', '&']) {
+		result += &rest[..i];
+		rest = &rest[i..];
+
+		// Keep the text of character data as is
+		if let Some(cdata) = rest.strip_prefix(CDATA_START) {
+			let end = cdata.find(CDATA_END).unwrap_or(cdata.len());
+			result += &cdata[..end];
+			rest = cdata.get(end + CDATA_END.len()..).unwrap_or_default();
+		}
+		// Remove comments
+		else if let Some(comment) = rest.strip_prefix(COMMENT_START) {
+			rest = match comment.find(COMMENT_END) {
+				Some(end) => &comment[end + COMMENT_END.len()..],
+				None => "",
+			};
+		}
+		// Remove tags
+		else if rest.starts_with('<') {
+			rest = match rest.find('>') {
+				Some(end) => &rest[end + 1..],
+				None => "",
+			};
+		}
+		// Decode entities, or keep the '&' if it's not one
+		else {
+			let entity = rest[1..].find(';')
+				.filter(|&end| end <= MAX_ENTITY_SIZE)
+				.map(|end| &rest[1..end + 1]);
+			let decoded = match entity {
+				Some("amp") => Some('&'),
+				Some("lt") => Some('<'),
+				Some("gt") => Some('>'),
+				Some("quot") => Some('"'),
+				Some("apos") => Some('\''),
+				Some(entity) => match entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+					Some(hex) => u32::from_str_radix(hex, 16).ok(),
+					None => entity.strip_prefix('#').and_then(|decimal| decimal.parse().ok()),
+				}.and_then(char::from_u32),
+				None => None,
+			};
+			match (decoded, entity) {
+				(Some(c), Some(entity)) => {
+					result.push(c);
+					rest = &rest[entity.len() + 2..];
+				},
+				_ => {
+					result.push('&');
+					rest = &rest[1..];
+				},
+			}
+		}
+	}
+	result += rest;
+
+	return result.trim().to_string();
+}
@@ -0,0 +1,1348 @@
+use core::time::Duration;
+use std::fs::File;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+
+/// Extensions of the videos which are ISO base media files, like MP4
+pub const MP4_EXTENSIONS: [&str; 3] = [".mp4", ".m4v", ".mov"];
+
+/// Largest movie box which is read, so a broken file isn't read into memory
+const MAX_MOVIE_SIZE: u64 = 64 << 20;
+/// Amount of files whose information is kept, which is more than the videos in most folders
+const CACHE_SIZE: usize = 1024;
+/// Amount of fast-start layouts which are kept, since each has a whole movie box
+const FAST_START_CACHE_SIZE: usize = 8;
+/// Amount of files which are remembered to not need a fast-start layout, like the ones which already start with
+/// the movie box
+const NOT_FAST_START_CACHE_SIZE: usize = 256;
+/// Most samples which are listed for a track, which is over 19 hours of video at 60 frames per second,
+/// so a small box which claims billions of samples isn't listed
+const MAX_SAMPLES: u64 = 1 << 22;
+
+
+/// Information of the files which were read recently, with the time they were modified
+static CACHE: Mutex<Vec<CacheEntry>> = Mutex::new(Vec::new());
+/// Fast-start layouts of the files which were streamed recently, with the time they were modified,
+/// where the layout is none if the file doesn't need one
+static FAST_START_CACHE: Mutex<Vec<FastStartCacheEntry>> = Mutex::new(Vec::new());
+
+
+/// Path of a file, the time it was modified, and its information, which is none if it isn't a valid MP4 file
+type CacheEntry = (PathBuf, SystemTime, Option<Arc<Mp4Info>>);
+/// Path of a file, the time it was modified, and its fast-start layout, which is none if it doesn't need one
+type FastStartCacheEntry = (PathBuf, SystemTime, Option<Arc<FastStart>>);
+
+
+/// Information from the movie box (`moov`) of an MP4 file
+pub struct Mp4Info
+{
+	/// Length of the movie, if it's known
+	pub duration: Option<Duration>,
+	/// Tracks in the original order
+	pub tracks: Vec<Mp4Track>,
+	/// Cover art in the metadata, like iTunes artwork
+	pub cover: Option<Mp4Cover>,
+}
+
+
+/// Image in the metadata (`moov/udta/meta/ilst/covr`), which is read from the file when it's requested
+pub struct Mp4Cover
+{
+	/// Position of the image in the file
+	pub offset: u64,
+	/// Size of the image in bytes
+	pub size: u64,
+	/// "image/jpeg" or "image/png"
+	pub content_type: &'static str,
+}
+
+
+/// Information from a track box (`trak`)
+pub struct Mp4Track
+{
+	pub kind: TrackKind,
+	/// Codec for the `codecs` parameter of a content type like "avc1.64001F" or "mp4a.40.2"
+	pub codec: String,
+	/// ISO 639-2 language like "eng", which is empty if it's undefined
+	pub language: String,
+	/// Width of the picture in pixels, which is 0 if it's not a video track
+	pub width: u32,
+	/// Height of the picture in pixels, which is 0 if it's not a video track
+	pub height: u32,
+}
+
+
+/// Kind of media in a track
+#[derive(Clone, Copy, PartialEq)]
+pub enum TrackKind
+{
+	Video,
+	Audio,
+	Text,
+	Other,
+}
+
+
+/// Layout of a file with the movie box after the media data, which is streamed as if the movie box were first,
+/// so players don't have to download the whole file to start
+pub struct FastStart
+{
+	/// Position of the first media data box, where the movie box is moved to
+	pub insert_at: u64,
+	/// Original position and size of the movie box
+	pub movie: BoxHeader,
+	/// Whole movie box with the chunk offsets changed for the new layout
+	pub movie_box: Arc<[u8]>,
+}
+
+
+/// A sample of a track, like a video frame
+#[derive(Clone, Copy)]
+pub struct Sample
+{
+	/// Position in the file
+	pub offset: u64,
+	/// Size in bytes
+	pub size: u32,
+	/// Decoding time in units of the media time scale
+	pub time: u64,
+	/// Length in units of the media time scale
+	pub duration: u32,
+	/// Difference between the presentation time and the decoding time
+	pub composition_offset: i32,
+	/// Whether decoding can start here, like a keyframe
+	pub is_sync: bool,
+}
+
+
+/// Type, position, and size of a box in a file
+#[derive(Clone, Copy)]
+pub struct BoxHeader
+{
+	/// Four characters like `moov`
+	pub kind: [u8; 4],
+	/// Position of the box in the file
+	pub offset: u64,
+	/// Size of the type and size fields
+	pub header_size: u64,
+	/// Size of the whole box including the header
+	pub size: u64,
+}
+
+
+/// Boxes in a box body which is in memory, like the tracks in the movie box
+pub struct Mp4Boxes<'a>
+{
+	data: &'a [u8],
+}
+
+
+impl Mp4Info
+{
+	/// Read the information of an MP4 file, which is cached until the file is modified
+	pub fn read(path: &Path) -> Option<Arc<Mp4Info>>
+	{
+		let modified = std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()?;
+
+		let mut cache = CACHE.lock().unwrap_or_else(|error| error.into_inner());
+		if let Some(i) = cache.iter().position(|(cached_path, cached_modified, _info)| cached_path == path && *cached_modified == modified) {
+			let entry = cache.remove(i);
+			let info = entry.2.clone();
+			cache.insert(0, entry);
+			return info;
+		}
+		drop(cache);
+
+		let info = File::open(path).ok()
+			.and_then(|mut file| read_movie_box(&mut file).ok().flatten())
+			.map(|(header, movie)| Arc::new(Mp4Info::parse(&movie, header.offset + header.header_size)));
+
+		// Keep the most recently used information, so the files which were deleted or renamed are forgotten
+		cache = CACHE.lock().unwrap_or_else(|error| error.into_inner());
+		cache.retain(|(cached_path, _modified, _info)| cached_path != path);
+		cache.insert(0, (path.to_path_buf(), modified, info.clone()));
+		cache.truncate(CACHE_SIZE);
+		return info;
+	}
+
+
+	/// Parse the body of the movie box, which is at the position in the file
+	pub fn parse(movie: &[u8], movie_offset: u64) -> Mp4Info
+	{
+		let duration = find_box(movie, b"mvhd")
+			.and_then(parse_time_header)
+			.and_then(|(timescale, duration, _rest)| to_duration(timescale, duration));
+
+		let tracks = Mp4Boxes::new(movie)
+			.filter(|child| &child.0 == b"trak")
+			.map(|(_kind, trak)| Mp4Track::parse(trak))
+			.collect();
+
+		let cover = find_path(movie, &[b"udta", b"meta"])
+			.and_then(|meta| find_box(skip_meta_header(meta), b"ilst"))
+			.and_then(|ilst| find_path(ilst, &[b"covr", b"data"]))
+			.and_then(|data| Mp4Cover::parse(data, movie_offset + (data.as_ptr() as usize - movie.as_ptr() as usize) as u64));
+
+		return Mp4Info {
+			duration,
+			tracks,
+			cover,
+		};
+	}
+
+
+	/// Get the first video track
+	pub fn video(&self) -> Option<&Mp4Track>
+	{
+		return self.tracks.iter().find(|track| track.kind == TrackKind::Video);
+	}
+
+
+	/// Get the languages of the audio tracks, where the undefined ones are skipped
+	pub fn audio_languages(&self) -> Vec<&str>
+	{
+		let mut result = Vec::new();
+		for track in &self.tracks {
+			if track.kind == TrackKind::Audio && !track.language.is_empty() && !result.contains(&track.language.as_str()) {
+				result.push(track.language.as_str());
+			}
+		}
+		return result;
+	}
+
+
+	/// Get the codecs of the video and audio tracks for the `codecs` parameter of a content type
+	pub fn codecs(&self) -> Vec<&str>
+	{
+		let mut result = Vec::new();
+		for track in &self.tracks {
+			if matches!(track.kind, TrackKind::Video | TrackKind::Audio) && !track.codec.is_empty() && !result.contains(&track.codec.as_str()) {
+				result.push(track.codec.as_str());
+			}
+		}
+		return result;
+	}
+}
+
+
+impl Mp4Cover
+{
+	/// Parse the body of a cover data box, which is at the position in the file
+	fn parse(data: &[u8], data_offset: u64) -> Option<Mp4Cover>
+	{
+		/// Position of the image after the type indicator and the locale
+		const IMAGE_OFFSET: usize = 8;
+		/// Type indicators of images
+		const JPEG: u32 = 13;
+		const PNG: u32 = 14;
+
+		let image = data.get(IMAGE_OFFSET..)?;
+
+		// Check the type indicator, or the signature if it's a generic type
+		let content_type = match read_u32(data, 0)? & 0xffffff {
+			JPEG => "image/jpeg",
+			PNG => "image/png",
+			_ if image.starts_with(b"\xff\xd8\xff") => "image/jpeg",
+			_ if image.starts_with(b"\x89PNG") => "image/png",
+			_ => return None,
+		};
+
+		if image.is_empty() {
+			return None;
+		}
+		return Some(Mp4Cover {
+			offset: data_offset + IMAGE_OFFSET as u64,
+			size: image.len() as u64,
+			content_type,
+		});
+	}
+
+
+	/// Read the image from the file
+	pub fn read(&self, file: &mut File) -> std::io::Result<Vec<u8>>
+	{
+		let mut result = vec![0; self.size as usize];
+		file.seek(SeekFrom::Start(self.offset))?;
+		file.read_exact(&mut result)?;
+		return Ok(result);
+	}
+}
+
+
+impl Mp4Track
+{
+	/// Parse the body of a track box
+	fn parse(trak: &[u8]) -> Mp4Track
+	{
+		let mdia = find_box(trak, b"mdia").unwrap_or_default();
+
+		let kind = TrackKind::from_media(mdia);
+
+		let language = find_box(mdia, b"mdhd")
+			.and_then(parse_time_header)
+			.and_then(|(_timescale, _duration, rest)| read_u16(rest, 0))
+			.map(decode_language)
+			.unwrap_or_default();
+
+		// Get the codec from the first sample description
+		let sample_entry = find_path(mdia, &[b"minf", b"stbl", b"stsd"])
+			.and_then(|stsd| stsd.get(8..))
+			.and_then(|entries| Mp4Boxes::new(entries).next());
+		let codec = match sample_entry {
+			Some((format, entry)) => parse_codec(kind, format, entry),
+			None => String::new(),
+		};
+
+		// Get the display size from the track header, which is a 16.16 fixed-point number
+		let (width, height) = match (kind, find_box(trak, b"tkhd")) {
+			(TrackKind::Video, Some(tkhd)) => {
+				let offset = match tkhd.first() {
+					Some(1) => 88,
+					_ => 76,
+				};
+				match (read_u32(tkhd, offset), read_u32(tkhd, offset + 4)) {
+					(Some(width), Some(height)) => (width >> 16, height >> 16),
+					_ => (0, 0),
+				}
+			},
+			_ => (0, 0),
+		};
+
+		return Mp4Track {
+			kind,
+			codec,
+			language,
+			width,
+			height,
+		};
+	}
+}
+
+
+impl TrackKind
+{
+	/// Get the kind from the handler of a media box (`mdia`)
+	pub fn from_media(mdia: &[u8]) -> TrackKind
+	{
+		return match find_box(mdia, b"hdlr").and_then(|hdlr| hdlr.get(8..12)) {
+			Some(b"vide") => TrackKind::Video,
+			Some(b"soun") => TrackKind::Audio,
+			Some(b"text" | b"sbtl" | b"subt") => TrackKind::Text,
+			_ => TrackKind::Other,
+		};
+	}
+}
+
+
+impl FastStart
+{
+	/// Get the layout of an MP4 file if its movie box is after the media data, which is cached until the file is modified
+	pub fn read(path: &Path) -> Option<Arc<FastStart>>
+	{
+		let modified = std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()?;
+
+		let mut cache = FAST_START_CACHE.lock().unwrap_or_else(|error| error.into_inner());
+		if let Some(i) = cache.iter().position(|(cached_path, cached_modified, _layout)| cached_path == path && *cached_modified == modified) {
+			let entry = cache.remove(i);
+			let layout = entry.2.clone();
+			cache.insert(0, entry);
+			return layout;
+		}
+		drop(cache);
+
+		let layout = File::open(path).ok()
+			.and_then(|mut file| FastStart::new(&mut file).ok().flatten())
+			.map(Arc::new);
+
+		// Keep the most recently used layouts, and remember more of the files which don't need one since they're small
+		cache = FAST_START_CACHE.lock().unwrap_or_else(|error| error.into_inner());
+		cache.retain(|(cached_path, _modified, _layout)| cached_path != path);
+		cache.insert(0, (path.to_path_buf(), modified, layout.clone()));
+		let mut layout_count = 0;
+		cache.retain(|(_path, _modified, layout)| {
+			layout_count += layout.is_some() as usize;
+			layout.is_none() || layout_count <= FAST_START_CACHE_SIZE
+		});
+		cache.truncate(NOT_FAST_START_CACHE_SIZE + FAST_START_CACHE_SIZE);
+		return layout;
+	}
+
+
+	/// Make the layout if the movie box is after the media data, unless the chunk offsets
+	/// wouldn't fit in 32 bits after moving the media data
+	fn new(file: &mut File) -> std::io::Result<Option<FastStart>>
+	{
+		// Skip reading the movie box if it's already before the media data
+		let boxes = read_top_level_boxes(file)?;
+		let insert_at = match boxes.iter().find(|header| &header.kind == b"mdat") {
+			Some(media) => media.offset,
+			None => return Ok(None),
+		};
+		match boxes.iter().find(|header| &header.kind == b"moov") {
+			Some(movie) if movie.offset > insert_at => (),
+			_ => return Ok(None),
+		}
+		let (movie, mut body) = match read_movie_box(file)? {
+			Some((movie, body)) => (movie, body),
+			None => return Ok(None),
+		};
+
+		// Move the chunks which are between the new and old positions of the movie box
+		for (position, is_64_bit) in find_chunk_offset_boxes(&body) {
+			let count = read_u32(&body, position + 4).unwrap_or_default() as usize;
+			let entry_size = match is_64_bit {
+				true => 8,
+				false => 4,
+			};
+			for i in 0..count {
+				let entry = position + 8 + i * entry_size;
+				let offset = match is_64_bit {
+					true => read_u64(&body, entry),
+					false => read_u32(&body, entry).map(u64::from),
+				};
+				let offset = match offset {
+					Some(offset) if (insert_at..movie.offset).contains(&offset) => offset + movie.size,
+					Some(_) => continue,
+					None => break,
+				};
+				match is_64_bit {
+					true => body[entry..entry + 8].copy_from_slice(&offset.to_be_bytes()),
+					false => match u32::try_from(offset) {
+						Ok(offset) => body[entry..entry + 4].copy_from_slice(&offset.to_be_bytes()),
+						Err(_) => return Ok(None),
+					},
+				}
+			}
+		}
+
+		// Put the header back on the body
+		let mut movie_box = Vec::with_capacity(movie.size as usize);
+		match movie.header_size {
+			16 => {
+				movie_box.extend(1u32.to_be_bytes());
+				movie_box.extend(b"moov");
+				movie_box.extend(movie.size.to_be_bytes());
+			},
+			_ => {
+				movie_box.extend((movie.size as u32).to_be_bytes());
+				movie_box.extend(b"moov");
+			},
+		}
+		movie_box.extend(body);
+
+		return Ok(Some(FastStart {
+			insert_at,
+			movie,
+			movie_box: movie_box.into(),
+		}));
+	}
+}
+
+
+impl<'a> Mp4Boxes<'a>
+{
+	/// Iterate the boxes in the data
+	pub fn new(data: &'a [u8]) -> Mp4Boxes<'a>
+	{
+		return Mp4Boxes {
+			data,
+		};
+	}
+}
+
+
+impl<'a> Iterator for Mp4Boxes<'a>
+{
+	/// Type and body of a box
+	type Item = ([u8; 4], &'a [u8]);
+
+	/// Get the next box, or stop at the end or an invalid box
+	fn next(&mut self) -> Option<Self::Item>
+	{
+		let kind = self.data.get(4..8)?.try_into().ok()?;
+		let (header_size, size) = match read_u32(self.data, 0)? {
+			0 => (8, self.data.len() as u64),
+			1 => (16, read_u64(self.data, 8)?),
+			size => (8, size as u64),
+		};
+		if size < header_size || size > self.data.len() as u64 {
+			self.data = &[];
+			return None;
+		}
+
+		let body = &self.data[header_size as usize .. size as usize];
+		self.data = &self.data[size as usize ..];
+		return Some((kind, body));
+	}
+}
+
+
+/// List the boxes at the top of the file, like `ftyp`, `moov`, and `mdat`
+pub fn read_top_level_boxes(file: &mut File) -> std::io::Result<Vec<BoxHeader>>
+{
+	let file_size = file.metadata()?.len();
+
+	let mut result = Vec::new();
+	let mut offset = 0;
+
+	while offset + 8 <= file_size {
+		let mut header = [0; 16];
+		let header_size = match file_size - offset {
+			8..16 => 8,
+			_ => 16,
+		};
+		file.seek(SeekFrom::Start(offset))?;
+		file.read_exact(&mut header[..header_size])?;
+
+		let kind = [header[4], header[5], header[6], header[7]];
+		let (header_size, size) = match u32::from_be_bytes([header[0], header[1], header[2], header[3]]) {
+			0 => (8, file_size - offset),
+			1 if header_size == 16 => (16, u64::from_be_bytes(header[8..16].try_into().unwrap())),
+			1 => return Err(std::io::ErrorKind::InvalidData.into()),
+			size => (8, size as u64),
+		};
+		if size < header_size || size > file_size - offset {
+			return Err(std::io::ErrorKind::InvalidData.into());
+		}
+
+		result.push(BoxHeader {
+			kind,
+			offset,
+			header_size,
+			size,
+		});
+		offset += size;
+	}
+
+	return Ok(result);
+}
+
+
+/// Read the header and body of the movie box, if the file has one
+pub fn read_movie_box(file: &mut File) -> std::io::Result<Option<(BoxHeader, Vec<u8>)>>
+{
+	let header = match read_top_level_boxes(file)?.into_iter().find(|header| &header.kind == b"moov") {
+		Some(header) => header,
+		None => return Ok(None),
+	};
+
+	let body_size = header.size - header.header_size;
+	if body_size > MAX_MOVIE_SIZE {
+		return Err(std::io::ErrorKind::InvalidData.into());
+	}
+
+	let mut body = vec![0; body_size as usize];
+	file.seek(SeekFrom::Start(header.offset + header.header_size))?;
+	file.read_exact(&mut body)?;
+	return Ok(Some((header, body)));
+}
+
+
+/// Get the positions of the bodies of the chunk offset boxes (`stco` and `co64`) in the movie body,
+/// and whether their offsets are 64-bit
+fn find_chunk_offset_boxes(movie: &[u8]) -> Vec<(usize, bool)>
+{
+	let mut result = Vec::new();
+	for (_kind, trak) in Mp4Boxes::new(movie).filter(|child| &child.0 == b"trak") {
+		let stbl = match find_path(trak, &[b"mdia", b"minf", b"stbl"]) {
+			Some(stbl) => stbl,
+			None => continue,
+		};
+		for (kind, body) in Mp4Boxes::new(stbl) {
+			let position = body.as_ptr() as usize - movie.as_ptr() as usize;
+			match &kind {
+				b"stco" => result.push((position, false)),
+				b"co64" => result.push((position, true)),
+				_ => (),
+			}
+		}
+	}
+	return result;
+}
+
+
+/// List the samples of a track from its sample table box (`stbl`), unless a table is missing or invalid
+pub fn read_samples(stbl: &[u8]) -> Option<Vec<Sample>>
+{
+	// Sizes, where a size which isn't 0 is the size of every sample
+	let stsz = find_box(stbl, b"stsz")?;
+	let constant_size = read_u32(stsz, 4)?;
+	let count = read_u32(stsz, 8)? as usize;
+	if constant_size == 0 && stsz.len() < 12 + count * 4 {
+		return None;
+	}
+
+	// Chunk positions
+	let chunk_offsets: Vec<u64> = match (find_box(stbl, b"stco"), find_box(stbl, b"co64")) {
+		(Some(stco), _) => (0..read_u32(stco, 4)? as usize).map(|i| read_u32(stco, 8 + i * 4).map(u64::from)).collect::<Option<_>>()?,
+		(None, Some(co64)) => (0..read_u32(co64, 4)? as usize).map(|i| read_u64(co64, 8 + i * 8)).collect::<Option<_>>()?,
+		(None, None) => return None,
+	};
+
+	// Samples per chunk as runs starting at chunk numbers
+	let stsc = find_box(stbl, b"stsc")?;
+	let run_count = read_u32(stsc, 4)? as usize;
+	let mut chunk_runs = Vec::new();
+	for run in 0..run_count {
+		let first_chunk = (read_u32(stsc, 8 + run * 12)? as usize).checked_sub(1)?;
+		let samples_per_chunk = read_u32(stsc, 12 + run * 12)?;
+		let end_chunk = match run + 1 < run_count {
+			true => (read_u32(stsc, 8 + (run + 1) * 12)? as usize).checked_sub(1)?,
+			false => chunk_offsets.len(),
+		};
+		chunk_runs.push((chunk_offsets.get(first_chunk..end_chunk)?, samples_per_chunk));
+	}
+
+	// Fail unless the chunks and the decoding times describe every sample, before making room for them
+	let stts = find_box(stbl, b"stts")?;
+	let timed_count = (0..read_u32(stts, 4)? as usize)
+		.map(|i| read_u32(stts, 8 + i * 8).map(u64::from))
+		.sum::<Option<u64>>()?;
+	let chunked_count = chunk_runs.iter()
+		.map(|(chunks, samples_per_chunk)| (chunks.len() as u64).saturating_mul(*samples_per_chunk as u64))
+		.fold(0, u64::saturating_add);
+	if count as u64 > timed_count.min(chunked_count).min(MAX_SAMPLES) {
+		return None;
+	}
+
+	// Decoding times as runs of durations
+	let mut durations = Vec::with_capacity(count);
+	for i in 0..read_u32(stts, 4)? as usize {
+		let run = read_u32(stts, 8 + i * 8)? as usize;
+		let duration = read_u32(stts, 12 + i * 8)?;
+		durations.extend(core::iter::repeat_n(duration, run.min(count - durations.len())));
+	}
+
+	// Composition offsets as runs, which are optional
+	let mut composition_offsets = Vec::new();
+	if let Some(ctts) = find_box(stbl, b"ctts") {
+		for i in 0..read_u32(ctts, 4)? as usize {
+			let run = read_u32(ctts, 8 + i * 8)? as usize;
+			let offset = read_u32(ctts, 12 + i * 8)? as i32;
+			composition_offsets.extend(core::iter::repeat_n(offset, run.min(count - composition_offsets.len())));
+		}
+	}
+	composition_offsets.resize(count, 0);
+
+	// Sync samples by number starting at 1, where every sample is a sync sample without the table
+	let mut is_sync = vec![true; count];
+	if let Some(stss) = find_box(stbl, b"stss") {
+		is_sync.fill(false);
+		for i in 0..read_u32(stss, 4)? as usize {
+			match (read_u32(stss, 8 + i * 4)? as usize).checked_sub(1) {
+				Some(number) if number < count => is_sync[number] = true,
+				_ => (),
+			}
+		}
+	}
+
+	let mut result = Vec::with_capacity(count);
+	let mut time = 0;
+	for (chunks, samples_per_chunk) in chunk_runs {
+		for &chunk_offset in chunks {
+			let mut offset = chunk_offset;
+			for _ in 0..samples_per_chunk {
+				let i = result.len();
+				if i == count {
+					return Some(result);
+				}
+				let size = match constant_size {
+					0 => read_u32(stsz, 12 + i * 4)?,
+					size => size,
+				};
+				result.push(Sample {
+					offset,
+					size,
+					time,
+					duration: durations[i],
+					composition_offset: composition_offsets[i],
+					is_sync: is_sync[i],
+				});
+				offset += size as u64;
+				time += durations[i] as u64;
+			}
+		}
+	}
+
+	return match result.len() == count {
+		true => Some(result),
+		false => None,
+	};
+}
+
+
+/// Add a box to the data
+pub fn write_box(data: &mut Vec<u8>, kind: &[u8; 4], body: &[u8])
+{
+	data.extend((8 + body.len() as u32).to_be_bytes());
+	data.extend(kind);
+	data.extend(body);
+}
+
+
+/// Get the body of the first child box with the type
+pub fn find_box<'a>(data: &'a [u8], kind: &[u8; 4]) -> Option<&'a [u8]>
+{
+	return Mp4Boxes::new(data)
+		.find(|child| &child.0 == kind)
+		.map(|(_kind, body)| body);
+}
+
+
+/// Get the body of a box inside other boxes, like `[b"minf", b"stbl"]`
+pub fn find_path<'a>(data: &'a [u8], kinds: &[&[u8; 4]]) -> Option<&'a [u8]>
+{
+	let mut result = data;
+	for kind in kinds {
+		result = find_box(result, kind)?;
+	}
+	return Some(result);
+}
+
+
+/// Get the boxes of a metadata box (`meta`), which has a version and flags in MP4 but not in QuickTime
+fn skip_meta_header(meta: &[u8]) -> &[u8]
+{
+	return match meta.get(4..8) {
+		Some(b"hdlr") => meta,
+		_ => meta.get(4..).unwrap_or_default(),
+	};
+}
+
+
+/// Read a big-endian number at the position
+pub fn read_u16(data: &[u8], offset: usize) -> Option<u16>
+{
+	return Some(u16::from_be_bytes(data.get(offset..offset + 2)?.try_into().ok()?));
+}
+
+
+/// Read a big-endian number at the position
+pub fn read_u32(data: &[u8], offset: usize) -> Option<u32>
+{
+	return Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().ok()?));
+}
+
+
+/// Read a big-endian number at the position
+pub fn read_u64(data: &[u8], offset: usize) -> Option<u64>
+{
+	return Some(u64::from_be_bytes(data.get(offset..offset + 8)?.try_into().ok()?));
+}
+
+
+/// Get the time scale, duration, and the rest of a movie or media header (`mvhd` or `mdhd`)
+pub fn parse_time_header(header: &[u8]) -> Option<(u32, u64, &[u8])>
+{
+	return match header.first()? {
+		1 => Some((read_u32(header, 20)?, read_u64(header, 24)?, header.get(32..)?)),
+		_ => Some((read_u32(header, 12)?, read_u32(header, 16)? as u64, header.get(20..)?)),
+	};
+}
+
+
+/// Convert units of the time scale to a duration, unless it's unknown or too long for a duration
+fn to_duration(timescale: u32, duration: u64) -> Option<Duration>
+{
+	if timescale == 0 || duration == 0 || duration == u32::MAX as u64 || duration == u64::MAX {
+		return None;
+	}
+	return Duration::try_from_secs_f64(duration as f64 / timescale as f64).ok();
+}
+
+
+/// Decode a packed ISO 639-2 language of a media header, where "und" and Macintosh languages are empty
+fn decode_language(packed: u16) -> String
+{
+	/// Undefined language ("und")
+	const UNDEFINED: u16 = 0x55c4;
+
+	if packed < 0x400 || packed == UNDEFINED {
+		return String::new();
+	}
+	return [10, 5, 0].iter()
+		.map(|shift| char::from(((packed >> shift) & 0x1f) as u8 + 0x60))
+		.collect();
+}
+
+
+/// Get the codec of a sample entry like "avc1.64001F" (RFC 6381)
+fn parse_codec(kind: TrackKind, format: [u8; 4], entry: &[u8]) -> String
+{
+	// Skip the fields before the child boxes
+	let children = match kind {
+		TrackKind::Video => entry.get(78..),
+		TrackKind::Audio => match read_u16(entry, 8) {
+			Some(1) => entry.get(44..),
+			Some(2) => entry.get(64..),
+			_ => entry.get(28..),
+		},
+		_ => None,
+	}.unwrap_or_default();
+
+	// Get the original format of encrypted media
+	let format = match &format {
+		b"encv" | b"enca" => find_path(children, &[b"sinf", b"frma"])
+			.and_then(|frma| frma.get(..4))
+			.and_then(|frma| frma.try_into().ok())
+			.unwrap_or(format),
+		_ => format,
+	};
+	let name = String::from_utf8_lossy(&format).into_owned();
+
+	let codec = match &format {
+		b"avc1" | b"avc3" => find_box(children, b"avcC")
+			.and_then(|avcc| avcc.get(1..4))
+			.map(|avcc| format!("{name}.{:02X}{:02X}{:02X}", avcc[0], avcc[1], avcc[2])),
+		b"hvc1" | b"hev1" => find_box(children, b"hvcC").and_then(|hvcc| parse_hevc_codec(&name, hvcc)),
+		b"av01" => find_box(children, b"av1C").and_then(parse_av1_codec),
+		b"vp09" => find_box(children, b"vpcC")
+			.and_then(|vpcc| vpcc.get(4..7))
+			.map(|vpcc| format!("vp09.{:02}.{:02}.{:02}", vpcc[0], vpcc[1], vpcc[2] >> 4)),
+		b"mp4a" => find_box(children, b"esds").and_then(parse_mpeg4_audio_codec),
+		b"Opus" => Some(String::from("opus")),
+		b"fLaC" => Some(String::from("flac")),
+		_ => None,
+	};
+
+	return codec.unwrap_or(name);
+}
+
+
+/// Get an HEVC codec like "hvc1.1.6.L93.B0" from its configuration (`hvcC`)
+fn parse_hevc_codec(name: &str, hvcc: &[u8]) -> Option<String>
+{
+	let profile_space = match hvcc.get(1)? >> 6 {
+		1 => "A",
+		2 => "B",
+		3 => "C",
+		_ => "",
+	};
+	let tier = match hvcc[1] & 0x20 {
+		0 => 'L',
+		_ => 'H',
+	};
+	let profile = hvcc[1] & 0x1f;
+	let compatibility = read_u32(hvcc, 2)?.reverse_bits();
+	let constraints = hvcc.get(6..12)?;
+	let level = hvcc.get(12)?;
+
+	let mut result = format!("{name}.{profile_space}{profile}.{compatibility:X}.{tier}{level}");
+	let used_constraints = constraints.iter().rposition(|&byte| byte != 0).map_or(0, |i| i + 1);
+	for byte in &constraints[..used_constraints] {
+		result += &format!(".{byte:X}");
+	}
+	return Some(result);
+}
+
+
+/// Get an AV1 codec like "av01.0.08M.08" from its configuration (`av1C`)
+fn parse_av1_codec(av1c: &[u8]) -> Option<String>
+{
+	let profile = av1c.get(1)? >> 5;
+	let level = av1c[1] & 0x1f;
+	let flags = av1c.get(2)?;
+	let tier = match flags & 0x80 {
+		0 => 'M',
+		_ => 'H',
+	};
+	let bit_depth = match (flags & 0x40 != 0, flags & 0x20 != 0) {
+		(true, true) => 12,
+		(true, false) => 10,
+		_ => 8,
+	};
+	return Some(format!("av01.{profile}.{level:02}{tier}.{bit_depth:02}"));
+}
+
+
+/// Get an MPEG-4 audio codec like "mp4a.40.2" from its elementary stream descriptor (`esds`)
+fn parse_mpeg4_audio_codec(esds: &[u8]) -> Option<String>
+{
+	/// Elementary stream descriptor
+	const ES_TAG: u8 = 3;
+	/// Decoder configuration descriptor
+	const DECODER_CONFIG_TAG: u8 = 4;
+	/// Decoder specific information descriptor
+	const DECODER_SPECIFIC_TAG: u8 = 5;
+	/// Object type of MPEG-4 audio, which has an audio object type
+	const MPEG4_AUDIO: u8 = 0x40;
+
+	// Skip the version and flags
+	let (tag, es) = read_descriptor(esds.get(4..)?)?;
+	if tag != ES_TAG {
+		return None;
+	}
+
+	// Skip the stream ID and the optional fields which the flags have
+	let flags = *es.get(2)?;
+	let mut offset = 3;
+	if flags & 0x80 != 0 {
+		offset += 2;
+	}
+	if flags & 0x40 != 0 {
+		offset += 1 + *es.get(offset)? as usize;
+	}
+	if flags & 0x20 != 0 {
+		offset += 2;
+	}
+
+	let (tag, decoder_config) = read_descriptor(es.get(offset..)?)?;
+	if tag != DECODER_CONFIG_TAG {
+		return None;
+	}
+	let object_type = *decoder_config.first()?;
+	if object_type != MPEG4_AUDIO {
+		return Some(format!("mp4a.{object_type:02x}"));
+	}
+
+	// Get the audio object type from the first 5 bits, or the next 6 bits if it's an escape value
+	let audio_object_type = match read_descriptor(decoder_config.get(13..)?) {
+		Some((DECODER_SPECIFIC_TAG, info)) => match info.first()? >> 3 {
+			31 => 32 + (((info[0] & 0x07) << 3) | (info.get(1)? >> 5)),
+			audio_object_type => audio_object_type,
+		},
+		_ => return Some(format!("mp4a.{object_type:02x}")),
+	};
+	return Some(format!("mp4a.{object_type:02x}.{audio_object_type}"));
+}
+
+
+/// Get the tag and body of an MPEG-4 descriptor, whose size has 7 bits per byte
+fn read_descriptor(data: &[u8]) -> Option<(u8, &[u8])>
+{
+	let tag = *data.first()?;
+	let mut size = 0;
+	let mut offset = 1;
+	for _ in 0..4 {
+		let byte = *data.get(offset)?;
+		offset += 1;
+		size = (size << 7) | (byte & 0x7f) as usize;
+		if byte & 0x80 == 0 {
+			break;
+		}
+	}
+	return Some((tag, data.get(offset..offset + size)?));
+}
+
+
+#[cfg(test)]
+pub mod tests
+{
+	use std::io::Write;
+
+	use super::*;
+
+
+	/// A track of a hand-built MP4 file
+	pub struct TestTrack
+	{
+		/// Handler like `vide` or `soun`
+		pub handler: &'static [u8; 4],
+		/// Units per second of the sample times
+		pub timescale: u32,
+		/// Size, duration, and whether it's a sync sample, for each sample
+		pub samples: Vec<(u32, u32, bool)>,
+		/// Amount of samples in each chunk
+		pub chunks: Vec<u32>,
+	}
+
+
+	/// Make the content of a sample, which starts with the track and sample numbers so it can be recognized
+	pub fn sample_data(track: usize, sample: usize, size: u32) -> Vec<u8>
+	{
+		let mut result = vec![track as u8, (sample >> 8) as u8, sample as u8];
+		result.resize(size as usize, 0xee);
+		return result;
+	}
+
+
+	/// Make an MP4 file with the file type, movie, and media data boxes, where the chunks of the tracks take turns
+	pub fn make_file(tracks: &[TestTrack], movie_at_end: bool, use_64_bit: bool) -> Vec<u8>
+	{
+		let mut file_type = Vec::new();
+		write_box(&mut file_type, b"ftyp", &[b"isom".as_slice(), &[0, 0, 2, 0], b"isom", b"mp41"].concat());
+
+		// Put the chunks in the media data, where the size of the movie box doesn't depend on the offsets
+		let movie_size = make_movie(tracks, &vec![Vec::new(); tracks.len()], use_64_bit).len();
+		let media_offset = match movie_at_end {
+			true => file_type.len() + 8,
+			false => file_type.len() + movie_size + 8,
+		};
+		let mut media = Vec::new();
+		let mut chunk_offsets = vec![Vec::new(); tracks.len()];
+		let mut first_samples = vec![0; tracks.len()];
+		for chunk in 0..tracks.iter().map(|track| track.chunks.len()).max().unwrap_or_default() {
+			for (i, track) in tracks.iter().enumerate() {
+				let Some(&count) = track.chunks.get(chunk) else {
+					continue;
+				};
+				chunk_offsets[i].push((media_offset + media.len()) as u64);
+				for sample in first_samples[i]..first_samples[i] + count as usize {
+					media.extend(sample_data(i, sample, track.samples[sample].0));
+				}
+				first_samples[i] += count as usize;
+			}
+		}
+
+		let mut media_data = Vec::new();
+		write_box(&mut media_data, b"mdat", &media);
+		let movie = make_movie(tracks, &chunk_offsets, use_64_bit);
+		return match movie_at_end {
+			true => [file_type, media_data, movie].concat(),
+			false => [file_type, movie, media_data].concat(),
+		};
+	}
+
+
+	/// Make a movie box with a movie time scale of 1000, given the chunk offsets of each track
+	pub fn make_movie(tracks: &[TestTrack], chunk_offsets: &[Vec<u64>], use_64_bit: bool) -> Vec<u8>
+	{
+		let mut body = Vec::new();
+		let mut mvhd = vec![0; 100];
+		mvhd[12..16].copy_from_slice(&1000u32.to_be_bytes());
+		let duration = tracks.iter()
+			.map(|track| track.samples.iter().map(|sample| sample.1 as u64).sum::<u64>() * 1000 / track.timescale as u64)
+			.max()
+			.unwrap_or_default();
+		mvhd[16..20].copy_from_slice(&(duration as u32).to_be_bytes());
+		write_box(&mut body, b"mvhd", &mvhd);
+
+		for (i, track) in tracks.iter().enumerate() {
+			let mut tkhd = vec![0; 84];
+			tkhd[12..16].copy_from_slice(&(i as u32 + 1).to_be_bytes());
+
+			let mut mdhd = vec![0; 24];
+			mdhd[12..16].copy_from_slice(&track.timescale.to_be_bytes());
+			let media_duration: u32 = track.samples.iter().map(|sample| sample.1).sum();
+			mdhd[16..20].copy_from_slice(&media_duration.to_be_bytes());
+
+			let mut hdlr = vec![0; 8];
+			hdlr.extend(track.handler);
+			hdlr.extend([0; 13]);
+
+			let mut mdia = Vec::new();
+			write_box(&mut mdia, b"mdhd", &mdhd);
+			write_box(&mut mdia, b"hdlr", &hdlr);
+			let mut minf = Vec::new();
+			write_box(&mut minf, b"stbl", &make_sample_table(track, &chunk_offsets[i], use_64_bit));
+			write_box(&mut mdia, b"minf", &minf);
+
+			let mut trak = Vec::new();
+			write_box(&mut trak, b"tkhd", &tkhd);
+			write_box(&mut trak, b"mdia", &mdia);
+			write_box(&mut body, b"trak", &trak);
+		}
+
+		let mut result = Vec::new();
+		write_box(&mut result, b"moov", &body);
+		return result;
+	}
+
+
+	/// Make the body of a sample table box, where the chunk offsets are 0 if they're missing
+	pub fn make_sample_table(track: &TestTrack, chunk_offsets: &[u64], use_64_bit: bool) -> Vec<u8>
+	{
+		let mut body = Vec::new();
+
+		let format = match track.handler {
+			b"vide" => b"avc1",
+			b"soun" => b"mp4a",
+			_ => b"tx3g",
+		};
+		let mut stsd = vec![0, 0, 0, 0, 0, 0, 0, 1];
+		write_box(&mut stsd, format, &[0, 0, 0, 0, 0, 0, 0, 1]);
+		write_box(&mut body, b"stsd", &stsd);
+
+		let mut stts = Vec::new();
+		let mut runs: Vec<(u32, u32)> = Vec::new();
+		for &(_size, duration, _is_sync) in &track.samples {
+			match runs.last_mut() {
+				Some((count, last)) if *last == duration => *count += 1,
+				_ => runs.push((1, duration)),
+			}
+		}
+		stts.extend([0; 4]);
+		stts.extend((runs.len() as u32).to_be_bytes());
+		for (count, duration) in runs {
+			stts.extend(count.to_be_bytes());
+			stts.extend(duration.to_be_bytes());
+		}
+		write_box(&mut body, b"stts", &stts);
+
+		if track.samples.iter().any(|sample| !sample.2) {
+			let numbers: Vec<u32> = (1..).zip(&track.samples).filter(|(_number, sample)| sample.2).map(|(number, _sample)| number).collect();
+			let mut stss = vec![0; 4];
+			stss.extend((numbers.len() as u32).to_be_bytes());
+			numbers.iter().for_each(|number| stss.extend(number.to_be_bytes()));
+			write_box(&mut body, b"stss", &stss);
+		}
+
+		let mut runs: Vec<(u32, u32)> = Vec::new();
+		for (chunk, &count) in (1..).zip(&track.chunks) {
+			match runs.last() {
+				Some(&(_first_chunk, last)) if last == count => (),
+				_ => runs.push((chunk, count)),
+			}
+		}
+		let mut stsc = vec![0; 4];
+		stsc.extend((runs.len() as u32).to_be_bytes());
+		for (first_chunk, count) in runs {
+			stsc.extend(first_chunk.to_be_bytes());
+			stsc.extend(count.to_be_bytes());
+			stsc.extend(1u32.to_be_bytes());
+		}
+		write_box(&mut body, b"stsc", &stsc);
+
+		// Sizes, which are one size for every sample if they're the same
+		let mut stsz = vec![0; 4];
+		match track.samples.windows(2).all(|pair| pair[0].0 == pair[1].0) && !track.samples.is_empty() {
+			true => {
+				stsz.extend(track.samples[0].0.to_be_bytes());
+				stsz.extend((track.samples.len() as u32).to_be_bytes());
+			},
+			false => {
+				stsz.extend([0; 4]);
+				stsz.extend((track.samples.len() as u32).to_be_bytes());
+				track.samples.iter().for_each(|sample| stsz.extend(sample.0.to_be_bytes()));
+			},
+		}
+		write_box(&mut body, b"stsz", &stsz);
+
+		let mut offsets = vec![0; 4];
+		offsets.extend((track.chunks.len() as u32).to_be_bytes());
+		for i in 0..track.chunks.len() {
+			let offset = chunk_offsets.get(i).copied().unwrap_or_default();
+			match use_64_bit {
+				true => offsets.extend(offset.to_be_bytes()),
+				false => offsets.extend((offset as u32).to_be_bytes()),
+			}
+		}
+		match use_64_bit {
+			true => write_box(&mut body, b"co64", &offsets),
+			false => write_box(&mut body, b"stco", &offsets),
+		}
+
+		return body;
+	}
+
+
+	/// Write a file in the temporary folder and open it, where the name is unique to the test
+	pub fn write_temporary_file(name: &str, data: &[u8]) -> (PathBuf, File)
+	{
+		let path = std::env::temp_dir().join(format!("seeslug-{}-{name}.mp4", std::process::id()));
+		File::create(&path).and_then(|mut file| file.write_all(data)).unwrap();
+		let file = File::open(&path).unwrap();
+		return (path, file);
+	}
+
+
+	/// Get the sample tables of the tracks in a movie box body
+	pub fn find_sample_tables(movie: &[u8]) -> Vec<&[u8]>
+	{
+		return Mp4Boxes::new(movie)
+			.filter(|child| &child.0 == b"trak")
+			.filter_map(|(_kind, trak)| find_path(trak, &[b"mdia", b"minf", b"stbl"]))
+			.collect();
+	}
+
+
+	/// Check that the samples of a track point to their content in the file
+	pub fn check_samples(file: &[u8], samples: &[Sample], track: usize, first_sample: usize)
+	{
+		for (i, sample) in samples.iter().enumerate() {
+			let begin = sample.offset as usize;
+			assert_eq!(&file[begin..begin + sample.size as usize], sample_data(track, first_sample + i, sample.size), "sample {i} of track {track}");
+		}
+	}
+
+
+	/// A video track with 0.5 second samples of different sizes and a keyframe every 2 seconds
+	pub fn video_track(count: usize) -> TestTrack
+	{
+		return TestTrack {
+			handler: b"vide",
+			timescale: 1000,
+			samples: (0..count).map(|i| (40 + i as u32 % 7, 500, i % 4 == 0)).collect(),
+			chunks: vec![2; count / 2],
+		};
+	}
+
+
+	/// An audio track with 0.25 second samples of the same size, which are all sync samples
+	pub fn audio_track(count: usize) -> TestTrack
+	{
+		return TestTrack {
+			handler: b"soun",
+			timescale: 48000,
+			samples: vec![(20, 12000, true); count],
+			chunks: vec![4; count / 4],
+		};
+	}
+
+
+	#[test]
+	fn samples_of_chunks()
+	{
+		let track = TestTrack {
+			handler: b"vide",
+			timescale: 1000,
+			samples: vec![(10, 100, true), (11, 100, false), (12, 200, false), (13, 100, true), (14, 100, false), (15, 100, false), (16, 100, false), (17, 100, false)],
+			chunks: vec![3, 3, 1, 1],
+		};
+		let file = make_file(&[track], false, false);
+		let movie = find_box(&file, b"moov").unwrap();
+		let samples = read_samples(find_sample_tables(movie)[0]).unwrap();
+
+		assert_eq!(samples.len(), 8);
+		check_samples(&file, &samples, 0, 0);
+		let times: Vec<u64> = samples.iter().map(|sample| sample.time).collect();
+		assert_eq!(times, [0, 100, 200, 400, 500, 600, 700, 800]);
+		let syncs: Vec<bool> = samples.iter().map(|sample| sample.is_sync).collect();
+		assert_eq!(syncs, [true, false, false, true, false, false, false, false]);
+		assert_eq!(samples[1].offset, samples[0].offset + 10);
+		assert_eq!(samples[2].offset, samples[1].offset + 11);
+	}
+
+
+	#[test]
+	fn samples_of_constant_size_and_64_bit_offsets()
+	{
+		let file = make_file(&[audio_track(12)], false, true);
+		let movie = find_box(&file, b"moov").unwrap();
+		let stbl = find_sample_tables(movie)[0];
+		assert!(find_box(stbl, b"co64").is_some());
+		assert_eq!(read_u32(find_box(stbl, b"stsz").unwrap(), 4), Some(20));
+
+		let samples = read_samples(stbl).unwrap();
+		assert_eq!(samples.len(), 12);
+		check_samples(&file, &samples, 0, 0);
+		assert!(samples.iter().all(|sample| sample.is_sync && sample.duration == 12000));
+	}
+
+
+	#[test]
+	fn samples_with_composition_offsets()
+	{
+		// Runs of offsets, where the last run is longer than the samples left and the offsets can be negative
+		let track = video_track(6);
+		let mut ctts = vec![1, 0, 0, 0, 0, 0, 0, 3];
+		for (run, offset) in [(1u32, 1000i32), (2, -500), (9, 0)] {
+			ctts.extend(run.to_be_bytes());
+			ctts.extend(offset.to_be_bytes());
+		}
+		let mut stbl = make_sample_table(&track, &[100, 200, 300], false);
+		write_box(&mut stbl, b"ctts", &ctts);
+
+		let samples = read_samples(&stbl).unwrap();
+		let offsets: Vec<i32> = samples.iter().map(|sample| sample.composition_offset).collect();
+		assert_eq!(offsets, [1000, -500, -500, 0, 0, 0]);
+	}
+
+
+	#[test]
+	fn samples_of_invalid_tables()
+	{
+		let track = video_track(8);
+		let stbl = make_sample_table(&track, &[100, 200, 300, 400], false);
+		assert_eq!(read_samples(&stbl).map(|samples| samples.len()), Some(8));
+
+		// Replace a box of the sample table with another body
+		let replace = |kind: &[u8; 4], body: &[u8]| {
+			let mut result = Vec::new();
+			for (child_kind, child) in Mp4Boxes::new(&stbl) {
+				match &child_kind == kind {
+					true => write_box(&mut result, kind, body),
+					false => write_box(&mut result, &child_kind, child),
+				}
+			}
+			return result;
+		};
+		let table = |entries: &[u32]| [&[0, 0, 0, 0][..], &((entries.len() / 3) as u32).to_be_bytes(), &entries.iter().flat_map(|entry| entry.to_be_bytes()).collect::<Vec<u8>>()].concat();
+
+		// Chunk numbers start at 1
+		assert!(read_samples(&replace(b"stsc", &table(&[0, 2, 1]))).is_none());
+		// A run of chunks after the last chunk
+		assert!(read_samples(&replace(b"stsc", &table(&[1, 2, 1, 9, 2, 1]))).is_none());
+		// Chunks which don't have all of the samples
+		assert!(read_samples(&replace(b"stsc", &table(&[1, 1, 1]))).is_none());
+		// Chunks which are missing
+		assert!(read_samples(&replace(b"stco", &[0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 100, 0, 0, 0, 200])).is_none());
+		// A table which claims more entries than it has
+		assert!(read_samples(&replace(b"stco", &[0, 0, 0, 0, 0, 0, 0, 9, 0, 0, 0, 100])).is_none());
+		// Decoding times which don't have all of the samples
+		assert!(read_samples(&replace(b"stts", &[0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 3, 0, 0, 0, 10])).is_none());
+	}
+
+
+	#[test]
+	fn samples_of_huge_count()
+	{
+		// A constant size with billions of samples, which the other tables don't describe
+		let track = audio_track(4);
+		let mut stbl = Vec::new();
+		for (kind, child) in Mp4Boxes::new(&make_sample_table(&track, &[100], false)) {
+			match &kind {
+				b"stsz" => write_box(&mut stbl, &kind, &[0, 0, 0, 0, 0, 0, 0, 20, 0xff, 0xff, 0xff, 0xff]),
+				_ => write_box(&mut stbl, &kind, child),
+			}
+		}
+		assert!(read_samples(&stbl).is_none());
+
+		// The other tables describe them too, but they're too many
+		let mut stbl = Vec::new();
+		for (kind, child) in Mp4Boxes::new(&make_sample_table(&track, &[100], false)) {
+			match &kind {
+				b"stsz" => write_box(&mut stbl, &kind, &[0, 0, 0, 0, 0, 0, 0, 20, 0xff, 0xff, 0xff, 0xff]),
+				b"stts" => write_box(&mut stbl, &kind, &[0, 0, 0, 0, 0, 0, 0, 1, 0xff, 0xff, 0xff, 0xff, 0, 0, 0, 1]),
+				b"stsc" => write_box(&mut stbl, &kind, &[0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1, 0xff, 0xff, 0xff, 0xff, 0, 0, 0, 1]),
+				_ => write_box(&mut stbl, &kind, child),
+			}
+		}
+		assert!(read_samples(&stbl).is_none());
+	}
+
+
+	#[test]
+	fn fast_start_offsets()
+	{
+		for use_64_bit in [false, true] {
+			let tracks = [video_track(8), audio_track(16)];
+			let data = make_file(&tracks, true, use_64_bit);
+			let (path, mut file) = write_temporary_file(&format!("fast-start-{use_64_bit}"), &data);
+			let layout = FastStart::new(&mut file).unwrap().unwrap();
+			std::fs::remove_file(path).unwrap();
+
+			// The movie box is moved before the media data, which moves the media data after it
+			let boxes: Vec<[u8; 4]> = Mp4Boxes::new(&data).map(|(kind, _body)| kind).collect();
+			assert_eq!(boxes, [*b"ftyp", *b"mdat", *b"moov"]);
+			assert_eq!(layout.insert_at, 8 + find_box(&data, b"ftyp").unwrap().len() as u64);
+			assert_eq!(layout.movie.offset + layout.movie.size, data.len() as u64);
+			let start = layout.insert_at as usize;
+			let end = layout.movie.offset as usize;
+			let streamed = [&data[..start], &layout.movie_box, &data[start..end]].concat();
+			assert_eq!(streamed.len(), data.len());
+
+			let movie = find_box(&streamed, b"moov").unwrap();
+			for (i, stbl) in find_sample_tables(movie).into_iter().enumerate() {
+				check_samples(&streamed, &read_samples(stbl).unwrap(), i, 0);
+			}
+		}
+	}
+
+
+	#[test]
+	fn fast_start_of_movie_first()
+	{
+		let data = make_file(&[video_track(8)], false, false);
+		let (path, mut file) = write_temporary_file("fast-start-first", &data);
+		assert!(FastStart::new(&mut file).unwrap().is_none());
+		std::fs::remove_file(path).unwrap();
+	}
+}
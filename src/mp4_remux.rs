@@ -0,0 +1,1186 @@
+use core::ops::Range;
+use std::fs::File;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::mp4::*;
+
+
+/// Longest time in seconds of a chunk of samples from one track, so the tracks are interleaved for streaming
+const CHUNK_SECONDS: f64 = 1.0;
+/// Shortest time in seconds of a segment of a fragmented file, which ends at the next keyframe
+const SEGMENT_SECONDS: f64 = 6.0;
+/// Amount of fragmented layouts which are kept, since each has the headers of every fragment
+const FRAGMENTED_CACHE_SIZE: usize = 8;
+/// Amount of remuxed files which are kept, since each has the movie box of the new file
+const REMUX_CACHE_SIZE: usize = 16;
+
+
+/// Fragmented layouts of the files which were streamed recently, with the time they were modified
+static FRAGMENTED_CACHE: Mutex<Vec<(PathBuf, SystemTime, Arc<Fragmented>)>> = Mutex::new(Vec::new());
+/// Remuxed files which were streamed recently, since a player requests many ranges of the same file
+static REMUX_CACHE: Mutex<Vec<RemuxCacheEntry>> = Mutex::new(Vec::new());
+
+
+/// Writes something else instead of a box and returns true, given the type and body of the box
+type ReplaceBox<'a> = dyn FnMut(&[u8; 4], &[u8], &mut Vec<u8>) -> bool + 'a;
+
+
+/// Which part of a video to keep
+pub struct RemuxOptions<'a>
+{
+	/// Time in seconds to start at, which moves back to the previous keyframe of the video
+	pub start: f64,
+	/// Time in seconds to end at, or none for the end of the video
+	pub end: Option<f64>,
+	/// Kinds of tracks to keep
+	pub kinds: &'a [TrackKind],
+	/// Major brand of the file type like `M4A `, or none for the original file type
+	pub brand: Option<&'a [u8; 4]>,
+}
+
+
+/// A standalone MP4 file made from part of another one without re-encoding
+pub struct Remux
+{
+	/// File type box, movie box, and the header of the media data box
+	pub head: Arc<[u8]>,
+	/// Positions and sizes of the media data in the original file, in order
+	pub media: Vec<(u64, u64)>,
+}
+
+
+/// A fragmented MP4 file made from the video and audio of another one without re-encoding, which is split
+/// into segments starting at keyframes for HTTP Live Streaming (HLS)
+pub struct Fragmented
+{
+	/// The initialization segment, then the headers of each fragment
+	pub headers: Arc<[u8]>,
+	/// Portions of the file in order
+	pub parts: Vec<FragmentedPart>,
+	/// Size of the initialization segment at the start, which has the file type and movie boxes
+	pub init_size: u64,
+	pub segments: Vec<Segment>,
+}
+
+
+/// A portion of a fragmented file
+pub enum FragmentedPart
+{
+	/// Part of the headers
+	Header
+	{
+		begin: usize,
+		length: usize,
+	},
+	/// Media data in the original file
+	Media
+	{
+		begin: u64,
+		length: u64,
+	},
+}
+
+
+/// A movie fragment and its media data, which can be played after the initialization segment
+pub struct Segment
+{
+	/// Position in the fragmented file
+	pub offset: u64,
+	/// Size in bytes
+	pub size: u64,
+	/// Length in seconds
+	pub duration: f64,
+}
+
+
+/// A remuxed file with the original file, the time it was modified, and the options it was made with
+struct RemuxCacheEntry
+{
+	path: PathBuf,
+	modified: SystemTime,
+	options: RemuxKey,
+	remux: Arc<Remux>,
+}
+
+
+/// Options of a remuxed file which can be compared
+#[derive(PartialEq)]
+struct RemuxKey
+{
+	start: f64,
+	end: Option<f64>,
+	kinds: Vec<TrackKind>,
+	brand: Option<[u8; 4]>,
+}
+
+
+/// The file type and movie of an MP4 file which can be remuxed
+struct Source
+{
+	/// Body of the file type box
+	file_type: Vec<u8>,
+	/// Body of the movie box
+	movie: Vec<u8>,
+	/// Units per second of the movie time scale
+	timescale: u32,
+	/// Length of the movie in units of the time scale
+	duration: u64,
+}
+
+
+/// A track which is kept
+struct RemuxTrack<'a>
+{
+	/// Original track box
+	trak: &'a [u8],
+	kind: TrackKind,
+	/// Units per second of the sample times
+	timescale: u32,
+	/// Time where the new file starts in units of the time scale
+	start: u64,
+	/// Whether the original had a sync sample table, since every sample is a sync sample without it
+	has_sync_table: bool,
+	samples: Vec<Sample>,
+	/// Positions of the chunks in the new file
+	chunk_offsets: Vec<u64>,
+	/// Amount of samples in each chunk
+	chunk_sizes: Vec<u32>,
+}
+
+
+/// How a track lines up with the start of the new file
+struct TrackEdit
+{
+	/// Time in units of the movie time scale before the track starts
+	delay: u64,
+	/// Time in units of the media time scale where the track is shown from
+	media_time: u64,
+	/// Time in units of the movie time scale which the track is shown for
+	duration: u64,
+}
+
+
+impl Remux
+{
+	/// Get the file made from part of an MP4 file, which is cached until the file is modified, unless it has nothing
+	/// in the time range or it can't be remuxed
+	pub fn read(path: &Path, options: &RemuxOptions) -> Option<Arc<Remux>>
+	{
+		let modified = std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()?;
+		let key = RemuxKey {
+			start: options.start,
+			end: options.end,
+			kinds: options.kinds.to_vec(),
+			brand: options.brand.copied(),
+		};
+
+		let mut cache = REMUX_CACHE.lock().unwrap_or_else(|error| error.into_inner());
+		if let Some(i) = cache.iter().position(|entry| entry.path == path && entry.modified == modified && entry.options == key) {
+			let entry = cache.remove(i);
+			let remux = Arc::clone(&entry.remux);
+			cache.insert(0, entry);
+			return Some(remux);
+		}
+		drop(cache);
+
+		let remux = Arc::new(Remux::new(&mut File::open(path).ok()?, options).ok().flatten()?);
+
+		// Keep the most recently used files, where the older versions of the file are forgotten
+		cache = REMUX_CACHE.lock().unwrap_or_else(|error| error.into_inner());
+		cache.retain(|entry| entry.path != path || entry.modified == modified);
+		cache.insert(0, RemuxCacheEntry {
+			path: path.to_path_buf(),
+			modified,
+			options: key,
+			remux: Arc::clone(&remux),
+		});
+		cache.truncate(REMUX_CACHE_SIZE);
+		return Some(remux);
+	}
+
+
+	/// Make the file from part of an MP4 file, unless it has nothing in the time range or it can't be remuxed,
+	/// like a fragmented MP4 file
+	pub fn new(file: &mut File, options: &RemuxOptions) -> std::io::Result<Option<Remux>>
+	{
+		let mut source = match Source::read(file)? {
+			Some(source) => source,
+			None => return Ok(None),
+		};
+		if let Some(brand) = options.brand && source.file_type.len() >= 4 {
+			source.file_type[..4].copy_from_slice(brand);
+			source.file_type.extend(brand);
+		}
+		if options.start >= source.duration as f64 / source.timescale as f64 {
+			return Ok(None);
+		}
+		let (file_type, movie, movie_timescale) = (source.file_type, source.movie, source.timescale);
+
+		let mut tracks = read_tracks(&movie, options.kinds);
+		let video_index = tracks.iter().position(|track| track.kind == TrackKind::Video);
+
+		// Start at the keyframe of the video before the start time, so it can be decoded without the previous frames
+		let start = match video_index.map(|i| &tracks[i]) {
+			Some(video) => {
+				let start_time = (options.start * video.timescale as f64) as u64;
+				video.samples.iter()
+					.rfind(|sample| sample.is_sync && sample.time <= start_time)
+					.map_or(0.0, |sample| sample.time as f64 / video.timescale as f64)
+			},
+			None => options.start,
+		};
+		let end = options.end.unwrap_or(f64::INFINITY);
+
+		// Keep the samples in the time range, starting with a sync sample
+		for track in &mut tracks {
+			let timescale = track.timescale as f64;
+			track.start = (start * timescale).round() as u64;
+			track.samples.retain(|sample| (sample.time + sample.duration as u64) as f64 / timescale > start && (sample.time as f64 / timescale) < end);
+			let first_sync = track.samples.iter().position(|sample| sample.is_sync).unwrap_or(track.samples.len());
+			track.samples.drain(..first_sync);
+		}
+		tracks.retain(|track| !track.samples.is_empty());
+		if tracks.is_empty() {
+			return Ok(None);
+		}
+
+		// Group the samples of each track into chunks, then interleave them by time
+		let mut chunks: Vec<(f64, usize, Range<usize>)> = Vec::new();
+		for (track_index, track) in tracks.iter_mut().enumerate() {
+			let chunk_length = (CHUNK_SECONDS * track.timescale as f64) as u64;
+			let mut chunk_start = 0;
+			for i in 1..=track.samples.len() {
+				if i == track.samples.len() || track.samples[i].time - track.samples[chunk_start].time >= chunk_length {
+					let time = track.samples[chunk_start].time as f64 / track.timescale as f64;
+					chunks.push((time, track_index, chunk_start..i));
+					track.chunk_sizes.push((i - chunk_start) as u32);
+					chunk_start = i;
+				}
+			}
+		}
+		chunks.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+		let media_size: u64 = tracks.iter()
+			.flat_map(|track| &track.samples)
+			.map(|sample| sample.size as u64)
+			.sum();
+		let media_header_size = match media_size + 8 > u32::MAX as u64 {
+			true => 16,
+			false => 8,
+		};
+
+		// Use 64-bit chunk offsets if the file is too big for 32-bit ones, where the size of the
+		// movie box doesn't depend on the offsets
+		let file_type_size = 8 + file_type.len() as u64;
+		let movie_size = write_movie(&movie, &mut tracks, &chunks, movie_timescale, false, 0).len() as u64;
+		let use_64_bit = file_type_size + movie_size + media_header_size + media_size > u32::MAX as u64;
+		let movie_size = write_movie(&movie, &mut tracks, &chunks, movie_timescale, use_64_bit, 0).len() as u64;
+		let media_offset = file_type_size + movie_size + media_header_size;
+		let movie = write_movie(&movie, &mut tracks, &chunks, movie_timescale, use_64_bit, media_offset);
+
+		let mut head = Vec::new();
+		write_box(&mut head, b"ftyp", &file_type);
+		head.extend(movie);
+		match media_header_size {
+			16 => {
+				head.extend(1u32.to_be_bytes());
+				head.extend(b"mdat");
+				head.extend((16 + media_size).to_be_bytes());
+			},
+			_ => {
+				head.extend((8 + media_size as u32).to_be_bytes());
+				head.extend(b"mdat");
+			},
+		}
+
+		// Copy the samples in the order of the chunks, joining the ones which are next to each other
+		let mut media = Vec::new();
+		for (_time, track_index, range) in &chunks {
+			add_media(&mut media, &tracks[*track_index].samples[range.clone()]);
+		}
+
+		return Ok(Some(Remux {
+			head: head.into(),
+			media,
+		}));
+	}
+}
+
+
+impl Fragmented
+{
+	/// Get the fragmented layout of an MP4 file, which is cached until the file is modified
+	pub fn read(path: &Path) -> Option<Arc<Fragmented>>
+	{
+		let modified = std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()?;
+
+		let mut cache = FRAGMENTED_CACHE.lock().unwrap_or_else(|error| error.into_inner());
+		if let Some(i) = cache.iter().position(|(cached_path, cached_modified, _layout)| cached_path == path && *cached_modified == modified) {
+			let entry = cache.remove(i);
+			let layout = Arc::clone(&entry.2);
+			cache.insert(0, entry);
+			return Some(layout);
+		}
+		drop(cache);
+
+		let layout = Arc::new(Fragmented::new(&mut File::open(path).ok()?).ok().flatten()?);
+
+		// Keep the most recently used layouts
+		cache = FRAGMENTED_CACHE.lock().unwrap_or_else(|error| error.into_inner());
+		cache.retain(|(cached_path, _modified, _layout)| cached_path != path);
+		cache.insert(0, (path.to_path_buf(), modified, Arc::clone(&layout)));
+		cache.truncate(FRAGMENTED_CACHE_SIZE);
+		return Some(layout);
+	}
+
+
+	/// Make the layout with the first video track and the first audio track, since players choose between
+	/// multiple tracks of the same kind differently, unless it has neither
+	fn new(file: &mut File) -> std::io::Result<Option<Fragmented>>
+	{
+		let source = match Source::read(file)? {
+			Some(source) => source,
+			None => return Ok(None),
+		};
+
+		let mut tracks = read_tracks(&source.movie, &[TrackKind::Video, TrackKind::Audio]);
+		let mut kinds = Vec::new();
+		tracks.retain(|track| {
+			let is_first = !kinds.contains(&track.kind) && !track.samples.is_empty();
+			kinds.push(track.kind);
+			return is_first;
+		});
+		if tracks.is_empty() {
+			return Ok(None);
+		}
+
+		// Start the segments at keyframes of the video, or split the audio evenly without video
+		let main = tracks.iter().find(|track| track.kind == TrackKind::Video).unwrap_or(&tracks[0]);
+		let timescale = main.timescale as f64;
+		let mut starts: Vec<f64> = vec![0.0];
+		for sample in &main.samples {
+			let time = sample.time as f64 / timescale;
+			if sample.is_sync && time - starts[starts.len() - 1] >= SEGMENT_SECONDS {
+				starts.push(time);
+			}
+		}
+		let end = main.samples.iter()
+			.map(|sample| (sample.time + sample.duration as u64) as f64 / timescale)
+			.fold(0.0, f64::max);
+
+		// Put each sample in the segment which has its time
+		let mut ranges = vec![Vec::new(); tracks.len()];
+		for (track, ranges) in tracks.iter().zip(&mut ranges) {
+			let mut begin = 0;
+			for i in 0..starts.len() {
+				let next_start = starts.get(i + 1).copied().unwrap_or(f64::INFINITY);
+				let length = track.samples[begin..].iter()
+					.take_while(|sample| (sample.time as f64 / track.timescale as f64) < next_start)
+					.count();
+				ranges.push(begin..begin + length);
+				begin += length;
+			}
+		}
+
+		let mut headers = Vec::new();
+		write_box(&mut headers, b"ftyp", &[b"iso5".as_slice(), &[0, 0, 2, 0], b"iso5", b"iso6", b"mp41"].concat());
+		write_fragmented_movie(&mut headers, &source.movie, &tracks);
+		let init_size = headers.len() as u64;
+
+		let mut parts = vec![FragmentedPart::Header { begin: 0, length: headers.len() }];
+		let mut segments = Vec::new();
+		let mut position = init_size;
+		for (i, &start) in starts.iter().enumerate() {
+			let samples: Vec<(&RemuxTrack, &[Sample])> = tracks.iter()
+				.zip(&ranges)
+				.map(|(track, ranges)| (track, &track.samples[ranges[i].clone()]))
+				.collect();
+			let media_size: u64 = samples.iter()
+				.flat_map(|(_track, samples)| *samples)
+				.map(|sample| sample.size as u64)
+				.sum();
+
+			// The data offsets are from the start of the movie fragment box, which doesn't change its size
+			let header_begin = headers.len();
+			let fragment_size = write_fragment(&mut Vec::new(), i as u32 + 1, &samples, 0).len();
+			let fragment = write_fragment(&mut headers, i as u32 + 1, &samples, fragment_size as u64 + 8);
+			let fragment_size = fragment.len() as u64;
+			headers.extend((8 + media_size as u32).to_be_bytes());
+			headers.extend(b"mdat");
+			parts.push(FragmentedPart::Header { begin: header_begin, length: headers.len() - header_begin });
+
+			let mut media = Vec::new();
+			for (_track, samples) in &samples {
+				add_media(&mut media, samples);
+			}
+			parts.extend(media.into_iter().map(|(begin, length)| FragmentedPart::Media { begin, length }));
+
+			let size = fragment_size + 8 + media_size;
+			let next_start = starts.get(i + 1).copied().unwrap_or(end);
+			segments.push(Segment {
+				offset: position,
+				size,
+				duration: (next_start - start).max(0.0),
+			});
+			position += size;
+		}
+
+		return Ok(Some(Fragmented {
+			headers: headers.into(),
+			parts,
+			init_size,
+			segments,
+		}));
+	}
+
+
+	/// Make an HLS playlist of the segments, given the URI of the fragmented file
+	pub fn playlist(&self, uri: &str) -> String
+	{
+		let target_duration = self.segments.iter()
+			.map(|segment| segment.duration.ceil() as u64)
+			.max()
+			.unwrap_or_default();
+
+		let mut result = format!(concat!(
+			"#EXTM3U\n",
+			"#EXT-X-VERSION:7\n",
+			"#EXT-X-TARGETDURATION:{}\n",
+			"#EXT-X-PLAYLIST-TYPE:VOD\n",
+			"#EXT-X-INDEPENDENT-SEGMENTS\n",
+			"#EXT-X-MAP:URI=\"{}\",BYTERANGE=\"{}@0\"\n",
+			), target_duration, uri, self.init_size);
+		for segment in &self.segments {
+			result += &format!("#EXTINF:{:.3},\n#EXT-X-BYTERANGE:{}@{}\n{}\n", segment.duration, segment.size, segment.offset, uri);
+		}
+		result += "#EXT-X-ENDLIST\n";
+		return result;
+	}
+}
+
+
+impl Source
+{
+	/// Read the file type and movie boxes, unless it isn't an MP4 file or it can't be remuxed, like a fragmented MP4 file
+	fn read(file: &mut File) -> std::io::Result<Option<Source>>
+	{
+		let boxes = read_top_level_boxes(file)?;
+		let file_type = match boxes.iter().find(|header| &header.kind == b"ftyp") {
+			Some(header) => {
+				let mut body = vec![0; (header.size - header.header_size) as usize];
+				file.seek(SeekFrom::Start(header.offset + header.header_size))?;
+				file.read_exact(&mut body)?;
+				body
+			},
+			None => [b"isom".as_slice(), &[0, 0, 2, 0], b"isom", b"iso2", b"mp41"].concat(),
+		};
+
+		let movie = match read_movie_box(file)? {
+			Some((_header, movie)) => movie,
+			None => return Ok(None),
+		};
+		if find_box(&movie, b"mvex").is_some() {
+			return Ok(None);
+		}
+		let (timescale, duration) = match find_box(&movie, b"mvhd").and_then(parse_time_header) {
+			Some((timescale, duration, _rest)) if timescale != 0 => (timescale, duration),
+			_ => return Ok(None),
+		};
+
+		return Ok(Some(Source {
+			file_type,
+			movie,
+			timescale,
+			duration,
+		}));
+	}
+}
+
+
+impl TrackEdit
+{
+	/// Get the edit which lines up a track with the start of the new file
+	fn new(track: &RemuxTrack, movie_timescale: u32) -> TrackEdit
+	{
+		let first_time = track.samples[0].time;
+
+		// The first sample shown can be after the first sample decoded, like the original probably had in its edit list
+		let first_presentation = track.samples.iter()
+			.take(16)
+			.map(|sample| (sample.time - first_time) as i64 + sample.composition_offset as i64)
+			.min()
+			.unwrap_or_default()
+			.max(0) as u64;
+
+		// Skip the part of the first sample before the start, like the first audio frame or subtitle
+		let skip = track.start.saturating_sub(first_time);
+
+		return TrackEdit {
+			delay: to_movie_time(first_time.saturating_sub(track.start), track.timescale, movie_timescale),
+			media_time: first_presentation + skip,
+			duration: to_movie_time(track_duration(track).saturating_sub(skip), track.timescale, movie_timescale),
+		};
+	}
+
+
+	/// Add an edit box (`edts`) with an edit list unless the track already lines up
+	fn write(&self, data: &mut Vec<u8>)
+	{
+		if self.delay == 0 && self.media_time == 0 {
+			return;
+		}
+
+		let mut entries = Vec::new();
+		if self.delay > 0 {
+			entries.push((self.delay, -1));
+		}
+		entries.push((self.duration, self.media_time as i64));
+
+		let elst = write_table(1, &entries, |entry, (duration, media_time)| {
+			entry.extend(duration.to_be_bytes());
+			entry.extend(media_time.to_be_bytes());
+			entry.extend(0x10000u32.to_be_bytes());
+		});
+		let mut edts = Vec::new();
+		write_box(&mut edts, b"elst", &elst);
+		write_box(data, b"edts", &edts);
+	}
+}
+
+
+/// Get the tracks of the movie with the kinds and all of their samples, in order
+fn read_tracks<'a>(movie: &'a [u8], kinds: &[TrackKind]) -> Vec<RemuxTrack<'a>>
+{
+	let mut result = Vec::new();
+	for (kind, trak) in Mp4Boxes::new(movie) {
+		if &kind != b"trak" {
+			continue;
+		}
+		let mdia = find_box(trak, b"mdia").unwrap_or_default();
+		let track_kind = TrackKind::from_media(mdia);
+		if !kinds.contains(&track_kind) {
+			continue;
+		}
+		let timescale = match find_box(mdia, b"mdhd").and_then(parse_time_header) {
+			Some((timescale, _duration, _rest)) if timescale != 0 => timescale,
+			_ => continue,
+		};
+		let stbl = find_path(mdia, &[b"minf", b"stbl"]).unwrap_or_default();
+		let samples = match read_samples(stbl) {
+			Some(samples) => samples,
+			None => continue,
+		};
+		result.push(RemuxTrack {
+			trak,
+			kind: track_kind,
+			timescale,
+			start: 0,
+			has_sync_table: find_box(stbl, b"stss").is_some(),
+			samples,
+			chunk_offsets: Vec::new(),
+			chunk_sizes: Vec::new(),
+		});
+	}
+	return result;
+}
+
+
+/// Add the positions and sizes of the samples in the original file, joining the ones which are next to each other
+fn add_media(media: &mut Vec<(u64, u64)>, samples: &[Sample])
+{
+	for sample in samples {
+		match media.last_mut() {
+			Some((offset, size)) if *offset + *size == sample.offset => *size += sample.size as u64,
+			_ => media.push((sample.offset, sample.size as u64)),
+		}
+	}
+}
+
+
+/// Add the movie box of a fragmented file with the tracks, which have no samples since they're in the fragments
+fn write_fragmented_movie(data: &mut Vec<u8>, movie: &[u8], tracks: &[RemuxTrack])
+{
+	let mut body = Vec::new();
+	let mut extends = Vec::new();
+	for (kind, child) in Mp4Boxes::new(movie) {
+		match &kind {
+			b"trak" => {
+				let track = match tracks.iter().find(|track| core::ptr::eq(track.trak, child)) {
+					Some(track) => track,
+					None => continue,
+				};
+
+				let trak = rewrite_boxes(child, &mut |kind, child, result| {
+					match kind {
+						b"stbl" => write_empty_sample_table(result, child),
+						_ => return false,
+					}
+					return true;
+				});
+				write_box(&mut body, &kind, &trak);
+
+				// Default to the first sample description in the fragments
+				let mut trex = vec![0; 4];
+				trex.extend(get_track_id(track).to_be_bytes());
+				trex.extend(1u32.to_be_bytes());
+				trex.extend([0; 12]);
+				write_box(&mut extends, b"trex", &trex);
+			},
+			_ => write_box(&mut body, &kind, child),
+		}
+	}
+	write_box(&mut body, b"mvex", &extends);
+	write_box(data, b"moov", &body);
+}
+
+
+/// Add a movie fragment box (`moof`) with the samples of each track, where the samples of the tracks are one after
+/// the other starting at the data offset from the start of the box, and get the box
+fn write_fragment<'a>(data: &'a mut Vec<u8>, sequence_number: u32, tracks: &[(&RemuxTrack, &[Sample])], data_offset: u64) -> &'a [u8]
+{
+	/// Sample which doesn't depend on others, like a keyframe
+	const SYNC_SAMPLE: u32 = 0x02000000;
+	/// Sample which depends on others and isn't a sync sample
+	const OTHER_SAMPLE: u32 = 0x01010000;
+
+	let mut body = Vec::new();
+	write_box(&mut body, b"mfhd", &[[0; 4], sequence_number.to_be_bytes()].concat());
+
+	let mut data_offset = data_offset;
+	for (track, samples) in tracks {
+		let first = match samples.first() {
+			Some(first) => first,
+			None => continue,
+		};
+		let mut traf = Vec::new();
+
+		// Positions are from the start of the movie fragment box
+		let mut tfhd = vec![0, 2, 0, 0];
+		tfhd.extend(get_track_id(track).to_be_bytes());
+		write_box(&mut traf, b"tfhd", &tfhd);
+
+		let mut tfdt = vec![1, 0, 0, 0];
+		tfdt.extend(first.time.to_be_bytes());
+		write_box(&mut traf, b"tfdt", &tfdt);
+
+		// Each sample has its duration, size, flags, and composition offset, where version 1 allows negative offsets
+		let has_offsets = track.samples.iter().any(|sample| sample.composition_offset != 0);
+		let version = match track.samples.iter().any(|sample| sample.composition_offset < 0) {
+			true => 1,
+			false => 0,
+		};
+		let flags: u32 = match has_offsets {
+			true => 0xf01,
+			false => 0x701,
+		};
+		let mut trun = vec![version, 0, 0, 0];
+		trun[1..].copy_from_slice(&flags.to_be_bytes()[1..]);
+		trun.extend((samples.len() as u32).to_be_bytes());
+		trun.extend((data_offset as u32).to_be_bytes());
+		for sample in *samples {
+			trun.extend(sample.duration.to_be_bytes());
+			trun.extend(sample.size.to_be_bytes());
+			let sample_flags = match sample.is_sync {
+				true => SYNC_SAMPLE,
+				false => OTHER_SAMPLE,
+			};
+			trun.extend(sample_flags.to_be_bytes());
+			if has_offsets {
+				trun.extend(sample.composition_offset.to_be_bytes());
+			}
+		}
+		write_box(&mut traf, b"trun", &trun);
+
+		write_box(&mut body, b"traf", &traf);
+		data_offset += samples.iter().map(|sample| sample.size as u64).sum::<u64>();
+	}
+
+	let begin = data.len();
+	write_box(data, b"moof", &body);
+	return &data[begin..];
+}
+
+
+/// Make the movie box with the kept tracks, where the media data starts at the position
+fn write_movie(movie: &[u8], tracks: &mut [RemuxTrack], chunks: &[(f64, usize, Range<usize>)], movie_timescale: u32,
+	use_64_bit: bool, media_offset: u64) -> Vec<u8>
+{
+	// Put the chunks in order
+	let mut position = media_offset;
+	for track in tracks.iter_mut() {
+		track.chunk_offsets.clear();
+	}
+	for (_time, track_index, range) in chunks {
+		let track = &mut tracks[*track_index];
+		track.chunk_offsets.push(position);
+		position += track.samples[range.clone()].iter().map(|sample| sample.size as u64).sum::<u64>();
+	}
+
+	let duration = tracks.iter()
+		.map(|track| {
+			let edit = TrackEdit::new(track, movie_timescale);
+			edit.delay + edit.duration
+		})
+		.max()
+		.unwrap_or_default();
+
+	let mut body = Vec::new();
+	let mut next_track = 0;
+	for (kind, child) in Mp4Boxes::new(movie) {
+		match &kind {
+			b"mvhd" => write_box(&mut body, &kind, &with_duration(child, 16, 24, duration)),
+			// Replace the tracks, which are in the original order
+			b"trak" => {
+				if let Some(track) = tracks.get(next_track) && core::ptr::eq(track.trak, child) {
+					write_track(&mut body, track, movie_timescale, use_64_bit);
+					next_track += 1;
+				}
+			},
+			_ => write_box(&mut body, &kind, child),
+		}
+	}
+
+	let mut result = Vec::new();
+	write_box(&mut result, b"moov", &body);
+	return result;
+}
+
+
+/// Add a track box with new sample tables
+fn write_track(data: &mut Vec<u8>, track: &RemuxTrack, movie_timescale: u32, use_64_bit: bool)
+{
+	let media_duration = track_duration(track);
+	let edit = TrackEdit::new(track, movie_timescale);
+
+	let body = rewrite_boxes(track.trak, &mut |kind, child, result| {
+		match kind {
+			b"tkhd" => {
+				write_box(result, kind, &with_duration(child, 20, 28, edit.delay + edit.duration));
+				edit.write(result);
+			},
+			b"edts" => (),
+			b"mdhd" => write_box(result, kind, &with_duration(child, 16, 24, media_duration)),
+			b"stbl" => write_sample_table(result, track, child, use_64_bit),
+			_ => return false,
+		}
+		return true;
+	});
+	write_box(data, b"trak", &body);
+}
+
+
+/// Copy the boxes and the ones in the media and media information boxes (`mdia` and `minf`), where
+/// the function can write something else instead of a box and return true
+fn rewrite_boxes(data: &[u8], replace: &mut ReplaceBox) -> Vec<u8>
+{
+	let mut result = Vec::new();
+	for (kind, child) in Mp4Boxes::new(data) {
+		if replace(&kind, child, &mut result) {
+			continue;
+		}
+		match &kind {
+			b"mdia" | b"minf" => {
+				let body = rewrite_boxes(child, replace);
+				write_box(&mut result, &kind, &body);
+			},
+			_ => write_box(&mut result, &kind, child),
+		}
+	}
+	return result;
+}
+
+
+/// Add a sample table box with the sample descriptions of the original and the kept samples
+fn write_sample_table(data: &mut Vec<u8>, track: &RemuxTrack, original: &[u8], use_64_bit: bool)
+{
+	let samples = &track.samples;
+	let mut body = Vec::new();
+
+	if let Some(stsd) = find_box(original, b"stsd") {
+		write_box(&mut body, b"stsd", stsd);
+	}
+
+	// Durations
+	let runs = count_runs(samples.iter().map(|sample| sample.duration));
+	write_box(&mut body, b"stts", &write_table(0, &runs, |entry, (count, duration)| {
+		entry.extend(count.to_be_bytes());
+		entry.extend(duration.to_be_bytes());
+	}));
+
+	// Composition offsets, where version 1 allows negative ones
+	if samples.iter().any(|sample| sample.composition_offset != 0) {
+		let version = match samples.iter().any(|sample| sample.composition_offset < 0) {
+			true => 1,
+			false => 0,
+		};
+		let runs = count_runs(samples.iter().map(|sample| sample.composition_offset));
+		write_box(&mut body, b"ctts", &write_table(version, &runs, |entry, (count, offset)| {
+			entry.extend(count.to_be_bytes());
+			entry.extend(offset.to_be_bytes());
+		}));
+	}
+
+	// Sync samples by number starting at 1
+	if track.has_sync_table {
+		let numbers: Vec<u32> = samples.iter()
+			.enumerate()
+			.filter(|(_i, sample)| sample.is_sync)
+			.map(|(i, _sample)| i as u32 + 1)
+			.collect();
+		write_box(&mut body, b"stss", &write_table(0, &numbers, |entry, number| entry.extend(number.to_be_bytes())));
+	}
+
+	// Samples per chunk as runs starting at chunk numbers
+	let mut runs = Vec::new();
+	for (i, &size) in track.chunk_sizes.iter().enumerate() {
+		match runs.last() {
+			Some(&(_first_chunk, last_size)) if last_size == size => (),
+			_ => runs.push((i as u32 + 1, size)),
+		}
+	}
+	write_box(&mut body, b"stsc", &write_table(0, &runs, |entry, (first_chunk, size)| {
+		entry.extend(first_chunk.to_be_bytes());
+		entry.extend(size.to_be_bytes());
+		entry.extend(1u32.to_be_bytes());
+	}));
+
+	// Sizes
+	let mut stsz = vec![0; 8];
+	stsz.extend((samples.len() as u32).to_be_bytes());
+	for sample in samples {
+		stsz.extend(sample.size.to_be_bytes());
+	}
+	write_box(&mut body, b"stsz", &stsz);
+
+	// Chunk positions
+	match use_64_bit {
+		true => write_box(&mut body, b"co64", &write_table(0, &track.chunk_offsets, |entry, offset| entry.extend(offset.to_be_bytes()))),
+		false => write_box(&mut body, b"stco", &write_table(0, &track.chunk_offsets, |entry, offset| entry.extend((*offset as u32).to_be_bytes()))),
+	}
+
+	write_box(data, b"stbl", &body);
+}
+
+
+/// Add a sample table box with the sample descriptions of the original and no samples
+fn write_empty_sample_table(data: &mut Vec<u8>, original: &[u8])
+{
+	let mut body = Vec::new();
+	if let Some(stsd) = find_box(original, b"stsd") {
+		write_box(&mut body, b"stsd", stsd);
+	}
+	write_box(&mut body, b"stts", &[0; 8]);
+	write_box(&mut body, b"stsc", &[0; 8]);
+	write_box(&mut body, b"stsz", &[0; 12]);
+	write_box(&mut body, b"stco", &[0; 8]);
+	write_box(data, b"stbl", &body);
+}
+
+
+/// Make the body of a full box with a version, no flags, an amount of entries, and the entries
+fn write_table<T>(version: u8, entries: &[T], write_entry: impl Fn(&mut Vec<u8>, &T)) -> Vec<u8>
+{
+	let mut result = vec![version, 0, 0, 0];
+	result.extend((entries.len() as u32).to_be_bytes());
+	for entry in entries {
+		write_entry(&mut result, entry);
+	}
+	return result;
+}
+
+
+/// Count the repeated values, like `[5, 5, 6]` is `[(2, 5), (1, 6)]`
+fn count_runs<T: PartialEq>(values: impl Iterator<Item = T>) -> Vec<(u32, T)>
+{
+	let mut result: Vec<(u32, T)> = Vec::new();
+	for value in values {
+		match result.last_mut() {
+			Some((count, last)) if *last == value => *count += 1,
+			_ => result.push((1, value)),
+		}
+	}
+	return result;
+}
+
+
+/// Copy a movie, track, or media header with a new duration, which is at different positions for versions 0 and 1
+fn with_duration(header: &[u8], position_0: usize, position_1: usize, duration: u64) -> Vec<u8>
+{
+	let mut result = header.to_vec();
+	match result.first() {
+		Some(1) if result.len() >= position_1 + 8 => result[position_1..position_1 + 8].copy_from_slice(&duration.to_be_bytes()),
+		Some(0) if result.len() >= position_0 + 4 => {
+			let duration = duration.min(u32::MAX as u64 - 1) as u32;
+			result[position_0..position_0 + 4].copy_from_slice(&duration.to_be_bytes());
+		},
+		_ => (),
+	}
+	return result;
+}
+
+
+/// Get the ID of a track from its header (`tkhd`), which is at different positions for versions 0 and 1
+fn get_track_id(track: &RemuxTrack) -> u32
+{
+	let tkhd = find_box(track.trak, b"tkhd").unwrap_or_default();
+	let position = match tkhd.first() {
+		Some(1) => 20,
+		_ => 12,
+	};
+	return read_u32(tkhd, position).unwrap_or(1);
+}
+
+
+/// Get the length of the kept samples of a track in units of its time scale
+fn track_duration(track: &RemuxTrack) -> u64
+{
+	return track.samples.iter().map(|sample| sample.duration as u64).sum();
+}
+
+
+/// Convert a time from the media time scale to the movie time scale
+fn to_movie_time(time: u64, timescale: u32, movie_timescale: u32) -> u64
+{
+	return (time as u128 * movie_timescale as u128 / timescale as u128) as u64;
+}
+
+
+#[cfg(test)]
+mod tests
+{
+	use crate::mp4::tests::*;
+
+	use super::*;
+
+
+	/// Remux a file made from the tracks, and get the remuxed file
+	fn remux(name: &str, tracks: &[TestTrack], options: &RemuxOptions) -> Option<Vec<u8>>
+	{
+		let data = make_file(tracks, false, false);
+		let (path, mut file) = write_temporary_file(name, &data);
+		let remux = Remux::new(&mut file, options).unwrap();
+		std::fs::remove_file(path).unwrap();
+
+		let remux = remux?;
+		let mut result = remux.head.to_vec();
+		for (begin, length) in remux.media {
+			result.extend(&data[begin as usize..(begin + length) as usize]);
+		}
+		return Some(result);
+	}
+
+
+	#[test]
+	fn remux_keeps_samples()
+	{
+		let options = RemuxOptions {
+			start: 2.2,
+			end: Some(7.0),
+			kinds: &[TrackKind::Video, TrackKind::Audio],
+			brand: None,
+		};
+		let data = remux("remux", &[video_track(20), audio_track(40)], &options).unwrap();
+
+		// The media data box is the rest of the file
+		let boxes: Vec<([u8; 4], usize)> = Mp4Boxes::new(&data).map(|(kind, body)| (kind, body.len())).collect();
+		assert_eq!(boxes.iter().map(|child| child.0).collect::<Vec<_>>(), [*b"ftyp", *b"moov", *b"mdat"]);
+		assert_eq!(boxes.iter().map(|child| child.1 + 8).sum::<usize>(), data.len());
+
+		// Both tracks start at the keyframe before the start time, which is at 2 seconds
+		let movie = find_box(&data, b"moov").unwrap();
+		let tables = find_sample_tables(movie);
+		assert_eq!(tables.len(), 2);
+		let video = read_samples(tables[0]).unwrap();
+		let audio = read_samples(tables[1]).unwrap();
+		assert_eq!(video.len(), 10);
+		assert_eq!(audio.len(), 20);
+		check_samples(&data, &video, 0, 4);
+		check_samples(&data, &audio, 1, 8);
+		assert!(video[0].is_sync && !video[1].is_sync && video[4].is_sync);
+	}
+
+
+	#[test]
+	fn remux_after_end()
+	{
+		let options = RemuxOptions {
+			start: 10.0,
+			end: None,
+			kinds: &[TrackKind::Video, TrackKind::Audio],
+			brand: None,
+		};
+		assert!(remux("remux-after-end", &[video_track(20)], &options).is_none());
+	}
+
+
+	#[test]
+	fn remux_audio()
+	{
+		let options = RemuxOptions {
+			start: 0.0,
+			end: None,
+			kinds: &[TrackKind::Audio],
+			brand: Some(b"M4A "),
+		};
+		let data = remux("remux-audio", &[video_track(8), audio_track(16)], &options).unwrap();
+
+		let file_type = find_box(&data, b"ftyp").unwrap();
+		assert_eq!(&file_type[..4], b"M4A ");
+		assert!(file_type[8..].chunks(4).any(|brand| brand == b"M4A "));
+
+		let movie = find_box(&data, b"moov").unwrap();
+		let tables = find_sample_tables(movie);
+		assert_eq!(tables.len(), 1);
+		let audio = read_samples(tables[0]).unwrap();
+		assert_eq!(audio.len(), 16);
+		check_samples(&data, &audio, 1, 0);
+	}
+
+
+	#[test]
+	fn remux_is_cached()
+	{
+		let data = make_file(&[video_track(20), audio_track(40)], false, false);
+		let (path, file) = write_temporary_file("remux-cache", &data);
+		let options = |start: f64| RemuxOptions {
+			start,
+			end: None,
+			kinds: &[TrackKind::Video, TrackKind::Audio],
+			brand: None,
+		};
+
+		let remux = Remux::read(&path, &options(1.0)).unwrap();
+		assert!(Arc::ptr_eq(&remux, &Remux::read(&path, &options(1.0)).unwrap()));
+		assert!(!Arc::ptr_eq(&remux, &Remux::read(&path, &options(3.0)).unwrap()));
+
+		// A modified file is remuxed again
+		drop(file);
+		let modified = SystemTime::now() + core::time::Duration::from_secs(10);
+		File::options().write(true).open(&path).and_then(|file| file.set_modified(modified)).unwrap();
+		assert!(!Arc::ptr_eq(&remux, &Remux::read(&path, &options(1.0)).unwrap()));
+		std::fs::remove_file(path).unwrap();
+	}
+
+
+	/// Make the fragmented layout of a file made from the tracks, and get the layout and the fragmented file
+	fn fragment(name: &str, tracks: &[TestTrack]) -> (Fragmented, Vec<u8>)
+	{
+		let data = make_file(tracks, true, false);
+		let (path, mut file) = write_temporary_file(name, &data);
+		let layout = Fragmented::new(&mut file).unwrap().unwrap();
+		std::fs::remove_file(path).unwrap();
+
+		let mut result = Vec::new();
+		for part in &layout.parts {
+			match *part {
+				FragmentedPart::Header { begin, length } => result.extend(&layout.headers[begin..begin + length]),
+				FragmentedPart::Media { begin, length } => result.extend(&data[begin as usize..(begin + length) as usize]),
+			}
+		}
+		return (layout, result);
+	}
+
+
+	#[test]
+	fn fragments_point_to_samples()
+	{
+		let tracks = [video_track(20), audio_track(40)];
+		let (layout, data) = fragment("fragmented", &tracks);
+
+		// The initialization segment has the tracks without samples, which are in the fragments
+		let init = &data[..layout.init_size as usize];
+		let boxes: Vec<[u8; 4]> = Mp4Boxes::new(init).map(|(kind, _body)| kind).collect();
+		assert_eq!(boxes, [*b"ftyp", *b"moov"]);
+		let movie = find_box(init, b"moov").unwrap();
+		assert_eq!(Mp4Boxes::new(find_box(movie, b"mvex").unwrap()).filter(|child| &child.0 == b"trex").count(), 2);
+		assert!(find_sample_tables(movie).into_iter().all(|stbl| read_samples(stbl).is_some_and(|samples| samples.is_empty())));
+
+		// Segments start at the keyframes after every 6 seconds, which are every 2 seconds
+		let durations: Vec<f64> = layout.segments.iter().map(|segment| segment.duration).collect();
+		assert_eq!(durations, [6.0, 4.0]);
+		assert_eq!(layout.segments[0].offset, layout.init_size);
+		let last = &layout.segments[layout.segments.len() - 1];
+		assert_eq!(last.offset + last.size, data.len() as u64);
+
+		// Each run of samples starts at its data offset from the start of the fragment, and starts at its decoding time
+		let mut next_samples = [0; 2];
+		for (i, segment) in layout.segments.iter().enumerate() {
+			let fragment = &data[segment.offset as usize..(segment.offset + segment.size) as usize];
+			let boxes: Vec<[u8; 4]> = Mp4Boxes::new(fragment).map(|(kind, _body)| kind).collect();
+			assert_eq!(boxes, [*b"moof", *b"mdat"]);
+			let moof = find_box(fragment, b"moof").unwrap();
+			assert_eq!(read_u32(find_box(moof, b"mfhd").unwrap(), 4), Some(i as u32 + 1));
+
+			for (_kind, traf) in Mp4Boxes::new(moof).filter(|child| &child.0 == b"traf") {
+				let track = read_u32(find_box(traf, b"tfhd").unwrap(), 4).unwrap() as usize - 1;
+				let first = next_samples[track];
+				let time: u32 = tracks[track].samples[..first].iter().map(|sample| sample.1).sum();
+				assert_eq!(read_u64(find_box(traf, b"tfdt").unwrap(), 4), Some(time as u64));
+
+				let trun = find_box(traf, b"trun").unwrap();
+				let count = read_u32(trun, 4).unwrap() as usize;
+				let mut position = read_u32(trun, 8).unwrap() as usize;
+				for j in 0..count {
+					let size = read_u32(trun, 16 + j * 12).unwrap();
+					assert_eq!(&fragment[position..position + size as usize], sample_data(track, first + j, size));
+					position += size as usize;
+				}
+				next_samples[track] += count;
+			}
+		}
+		assert_eq!(next_samples, [20, 40]);
+	}
+
+
+	#[test]
+	fn fragments_of_audio()
+	{
+		// Without video, the segments are split evenly
+		let (layout, data) = fragment("fragmented-audio", &[audio_track(60)]);
+		let durations: Vec<f64> = layout.segments.iter().map(|segment| segment.duration).collect();
+		assert_eq!(durations, [6.0, 6.0, 3.0]);
+		let last = &layout.segments[layout.segments.len() - 1];
+		assert_eq!(last.offset + last.size, data.len() as u64);
+	}
+
+
+	#[test]
+	fn fragments_of_text()
+	{
+		let track = TestTrack {
+			handler: b"text",
+			timescale: 1000,
+			samples: vec![(10, 1000, true); 4],
+			chunks: vec![4],
+		};
+		let data = make_file(&[track], false, false);
+		let (path, mut file) = write_temporary_file("fragmented-text", &data);
+		assert!(Fragmented::new(&mut file).unwrap().is_none());
+		std::fs::remove_file(path).unwrap();
+	}
+
+
+	#[test]
+	fn playlist_of_segments()
+	{
+		let (layout, _data) = fragment("playlist", &[video_track(20)]);
+		let playlist = layout.playlist("video.mp4?hls");
+		let lines: Vec<&str> = playlist.lines().collect();
+		assert_eq!(lines[0], "#EXTM3U");
+		assert!(lines.contains(&"#EXT-X-TARGETDURATION:6"));
+		assert!(lines.contains(&format!("#EXT-X-MAP:URI=\"video.mp4?hls\",BYTERANGE=\"{}@0\"", layout.init_size).as_str()));
+		let segment = &layout.segments[1];
+		let range = format!("#EXT-X-BYTERANGE:{}@{}", segment.size, segment.offset);
+		let index = lines.iter().position(|line| *line == range).unwrap();
+		assert_eq!(lines[index - 1], "#EXTINF:4.000,");
+		assert_eq!(lines[index + 1], "video.mp4?hls");
+		assert_eq!(lines[lines.len() - 1], "#EXT-X-ENDLIST");
+	}
+}
@@ -0,0 +1,101 @@
+use std::fs::File;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+use crate::mp4::*;
+use crate::webvtt::*;
+
+
+/// Formats of text tracks which can be converted to WebVTT, which are 3GPP timed text and WebVTT in MP4
+pub const TEXT_FORMATS: [&str; 2] = ["tx3g", "wvtt"];
+
+
+/// Convert a text track of an MP4 file to WebVTT given the index of its track box (`trak`),
+/// unless it isn't a text track in one of the formats
+pub fn read_text_track(file: &mut File, index: usize) -> std::io::Result<Option<String>>
+{
+	let movie = match read_movie_box(file)? {
+		Some((_header, movie)) => movie,
+		None => return Ok(None),
+	};
+	let trak = match Mp4Boxes::new(&movie).filter(|child| &child.0 == b"trak").nth(index) {
+		Some((_kind, trak)) => trak,
+		None => return Ok(None),
+	};
+
+	let mdia = find_box(trak, b"mdia").unwrap_or_default();
+	if TrackKind::from_media(mdia) != TrackKind::Text {
+		return Ok(None);
+	}
+	let timescale = match find_box(mdia, b"mdhd").and_then(parse_time_header) {
+		Some((timescale, _duration, _rest)) if timescale != 0 => timescale as f64,
+		_ => return Ok(None),
+	};
+	let stbl = find_path(mdia, &[b"minf", b"stbl"]).unwrap_or_default();
+	let format = find_box(stbl, b"stsd")
+		.and_then(|stsd| stsd.get(8..))
+		.and_then(|entries| Mp4Boxes::new(entries).next())
+		.map(|(format, _entry)| format);
+	let format = match format {
+		Some(format) if TEXT_FORMATS.contains(&String::from_utf8_lossy(&format).as_ref()) => format,
+		_ => return Ok(None),
+	};
+	let samples = match read_samples(stbl) {
+		Some(samples) => samples,
+		None => return Ok(None),
+	};
+
+	let mut cues = Vec::new();
+	for sample in samples {
+		let mut data = vec![0; sample.size as usize];
+		file.seek(SeekFrom::Start(sample.offset))?;
+		file.read_exact(&mut data)?;
+
+		let start = sample.time as f64 / timescale;
+		let end = (sample.time + sample.duration as u64) as f64 / timescale;
+
+		match &format {
+			// Each sample is a cue, where an empty one is a gap
+			b"tx3g" => add_cue(&mut cues, Cue {
+				start,
+				end,
+				settings: String::new(),
+				text: escape_webvtt(&parse_timed_text(&data)),
+			}),
+			// Each sample has the cues which are shown at the same time (`vttc`), or an empty box (`vtte`) for a gap
+			_ => for (kind, vttc) in Mp4Boxes::new(&data) {
+				if &kind != b"vttc" {
+					continue;
+				}
+				let text = find_box(vttc, b"payl").unwrap_or_default();
+				let settings = find_box(vttc, b"sttg").unwrap_or_default();
+				add_cue(&mut cues, Cue {
+					start,
+					end,
+					settings: String::from_utf8_lossy(settings).trim().to_string(),
+					text: String::from_utf8_lossy(text).trim().to_string(),
+				});
+			},
+		}
+	}
+
+	return Ok(Some(make_webvtt(&cues)));
+}
+
+
+/// Get the text of a 3GPP timed text sample, which is UTF-8, or UTF-16 with a byte order mark
+fn parse_timed_text(sample: &[u8]) -> String
+{
+	let length = read_u16(sample, 0).unwrap_or_default() as usize;
+	let text = sample.get(2..2 + length).unwrap_or_default();
+	return match text.strip_prefix(b"\xfe\xff") {
+		Some(utf16) => {
+			let units: Vec<u16> = utf16.chunks_exact(2)
+				.map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+				.collect();
+			String::from_utf16_lossy(&units)
+		},
+		None => String::from_utf8_lossy(text).into_owned(),
+	};
+}
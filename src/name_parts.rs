@@ -1,32 +1,85 @@
+use crate::languages::*;
+
+
+/// Extensions of subtitles, which can be after a language and ".default"
+pub const SUBTITLE_EXTENSIONS: [&str; 4] = [".vtt", ".srt", ".ass", ".ssa"];
+/// Part of the subtitle extension to enable it by default
+const DEFAULT_SUBTITLE: &str = ".default";
+
+
 /// The file name and extension
 pub struct NameParts<'a>
 {
-	/// Before the first dot
+	/// Before the extension, which can have dots like "Mr. Robot"
 	pub basename: &'a str,
-	/// The first dot until the end
+	/// The last dot until the end, or the chain of a subtitle like ".default.en.vtt" or ".en.ass"
 	pub extension: &'a str,
 }
 
 
 impl<'a> NameParts<'a>
 {
-	/// Split the name
+	/// Split the name from the right, so the dots in titles are part of the basename
 	pub fn new(name: &'a str) -> NameParts<'a>
 	{
 		let mut basename = name;
-		let mut extension = "";
 
-		for (i, c) in name.char_indices() {
-			if c == '.' {
-				basename = &name[..i];
-				extension = &name[i..];
-				break;
+		// Extension like ".mp4", ".vtt", or ".srt"
+		let last = get_last_suffix(basename);
+		basename = &basename[..basename.len() - last.len()];
+
+		// Optional language and ".default" before a subtitle extension like ".default.en.vtt"
+		if SUBTITLE_EXTENSIONS.contains(&last) {
+			let language = get_last_suffix(basename);
+			if is_language_tag(language.trim_start_matches('.')) {
+				basename = &basename[..basename.len() - language.len()];
+			}
+			if let Some(stripped) = basename.strip_suffix(DEFAULT_SUBTITLE) {
+				basename = stripped;
 			}
 		}
 
 		return NameParts {
 			basename,
-			extension,
+			extension: &name[basename.len()..],
 		};
 	}
 }
+
+
+/// Get the last dot until the end, for example "Mr. Robot.en" is ".en" and "Movie" is ""
+fn get_last_suffix(name: &str) -> &str
+{
+	return match name.rfind('.') {
+		Some(i) => &name[i..],
+		None => "",
+	};
+}
+
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+
+	/// Split a name into its basename and extension
+	fn split(name: &str) -> (&str, &str)
+	{
+		let parts = NameParts::new(name);
+		return (parts.basename, parts.extension);
+	}
+
+
+	#[test]
+	fn subtitle_languages()
+	{
+		assert_eq!(split("Show.en.srt"), ("Show", ".en.srt"));
+		assert_eq!(split("Show.default.pt-BR.vtt"), ("Show", ".default.pt-BR.vtt"));
+		assert_eq!(split("Mr. Robot.fil.ass"), ("Mr. Robot", ".fil.ass"));
+		// Words which aren't known languages are part of the basename
+		assert_eq!(split("Show.bad.srt"), ("Show.bad", ".srt"));
+		assert_eq!(split("Show.xyz.srt"), ("Show.xyz", ".srt"));
+		assert_eq!(split("Show.mp4"), ("Show", ".mp4"));
+	}
+}
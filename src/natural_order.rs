@@ -0,0 +1,70 @@
+use core::cmp::Ordering;
+
+
+/// Words at the start of a name which are skipped when sorting, like "The Matrix"
+const ARTICLES: [&str; 3] = ["The ", "A ", "An "];
+
+
+/// Compare names like people read them, where "2: Pilot" is before "10: Finale", letters are compared
+/// without case, and articles at the start are skipped, so "The Matrix" is with the other names starting with M
+pub fn compare_naturally(a: &str, b: &str) -> Ordering
+{
+	let mut rest_a = skip_article(a);
+	let mut rest_b = skip_article(b);
+
+	while let (Some(char_a), Some(char_b)) = (rest_a.chars().next(), rest_b.chars().next()) {
+		let ordering = match char_a.is_ascii_digit() && char_b.is_ascii_digit() {
+			// Compare the whole numbers
+			true => {
+				let (number_a, after_a) = split_number(rest_a);
+				let (number_b, after_b) = split_number(rest_b);
+				rest_a = after_a;
+				rest_b = after_b;
+				compare_numbers(number_a, number_b)
+			},
+			// Compare the letters without case
+			false => {
+				rest_a = &rest_a[char_a.len_utf8()..];
+				rest_b = &rest_b[char_b.len_utf8()..];
+				char_a.to_lowercase().cmp(char_b.to_lowercase())
+			},
+		};
+		if ordering != Ordering::Equal {
+			return ordering;
+		}
+	}
+
+	// Put the shorter name first, otherwise compare the bytes so different names are never equal
+	return rest_a.len().cmp(&rest_b.len()).then_with(|| a.cmp(b));
+}
+
+
+/// Get the name without an article at the start, unless that's the whole name
+fn skip_article(name: &str) -> &str
+{
+	for article in ARTICLES {
+		match name.get(..article.len()) {
+			Some(start) if start.eq_ignore_ascii_case(article) && name.len() > article.len() => return &name[article.len()..],
+			_ => (),
+		}
+	}
+
+	return name;
+}
+
+
+/// Split the digits at the start from the rest, for example "10: Finale" is "10" and ": Finale"
+fn split_number(text: &str) -> (&str, &str)
+{
+	let end = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+	return text.split_at(end);
+}
+
+
+/// Compare numbers of any length by value, where "007" is equal to "7"
+fn compare_numbers(a: &str, b: &str) -> Ordering
+{
+	let a = a.trim_start_matches('0');
+	let b = b.trim_start_matches('0');
+	return a.len().cmp(&b.len()).then_with(|| a.cmp(b));
+}
@@ -0,0 +1,526 @@
+use core::ffi::c_int;
+use core::ffi::c_uint;
+use core::time::Duration;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::ErrorKind::Interrupted;
+use std::io::ErrorKind::WouldBlock;
+use std::io::Read;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::os::fd::AsRawFd;
+use std::os::fd::FromRawFd;
+use std::os::fd::OwnedFd;
+use std::os::fd::RawFd;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::Sender;
+use std::time::Instant;
+
+use crate::arguments::*;
+use crate::http::*;
+use crate::print::*;
+use crate::request::*;
+use crate::response::*;
+use crate::server::*;
+use crate::shutdown::*;
+use crate::status::*;
+use crate::thread_pool::*;
+
+use Status::*;
+
+
+unsafe extern "C"
+{
+	// https://man7.org/linux/man-pages/man2/epoll_create.2.html
+	fn epoll_create1(flags: c_int) -> c_int;
+	// https://man7.org/linux/man-pages/man2/epoll_ctl.2.html
+	fn epoll_ctl(epfd: c_int, op: c_int, fd: c_int, event: *mut EpollEvent) -> c_int;
+	// https://man7.org/linux/man-pages/man2/epoll_wait.2.html
+	fn epoll_wait(epfd: c_int, events: *mut EpollEvent, maxevents: c_int, timeout: c_int) -> c_int;
+	// https://man7.org/linux/man-pages/man2/eventfd.2.html
+	fn eventfd(initval: c_uint, flags: c_int) -> c_int;
+}
+
+
+/// Close the file descriptor when the process executes another program (`EPOLL_CLOEXEC` and `EFD_CLOEXEC`)
+const CLOSE_ON_EXEC: c_int = 0x80000;
+/// Don't block when reading an event file (`EFD_NONBLOCK`)
+const EVENT_FILE_NON_BLOCKING: c_int = 0x800;
+/// Add a file descriptor to the interest list (`EPOLL_CTL_ADD`)
+const ADD: c_int = 1;
+/// Readable (`EPOLLIN`)
+const READABLE: u32 = 0x1;
+/// Writable (`EPOLLOUT`)
+const WRITABLE: u32 = 0x4;
+/// Peer closed its side of the connection (`EPOLLRDHUP`)
+const PEER_CLOSED: u32 = 0x2000;
+/// Only notify when the state changes (`EPOLLET`)
+const EDGE_TRIGGERED: u32 = 1 << 31;
+
+/// Identifies the event file which wakes the loop
+const WAKER_TOKEN: u64 = 0;
+/// Identifies the first listener, where the others follow
+const FIRST_LISTENER_TOKEN: u64 = 1;
+
+/// Maximum amount of events handled at once
+const MAX_EVENTS: usize = 256;
+/// Maximum time in milliseconds to wait for events before closing idle connections
+const WAIT_TIMEOUT: c_int = 1000;
+/// Maximum bytes written to a connection before letting the others have a turn
+const WRITE_LIMIT: u64 = 1 << 22;
+/// Time to wait before accepting again after it failed
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+
+/// Reads requests and writes responses for many connections with one thread,
+/// while the thread pool does the blocking filesystem work to make each response and send the files
+pub struct Reactor<'a>
+{
+	/// Event poll instance
+	epoll: OwnedFd,
+	/// Event file which wakes the loop when a response is ready
+	waker: Arc<File>,
+	/// Incoming connections, where each listener's token is its index after the first token
+	listeners: Vec<TcpListener>,
+	/// Open connections by token
+	connections: HashMap<u64, Connection>,
+	/// Token for the next connection
+	next_token: u64,
+	/// Connections which reached the write limit and can continue
+	ready: Vec<u64>,
+	/// Work which the thread pool finished
+	finished: Receiver<(u64, Finished)>,
+	/// Sender of finished work for the thread pool
+	sender: Sender<(u64, Finished)>,
+	/// Thread pool which makes the responses and sends the files
+	pool: &'a ThreadPool,
+	config: &'static Config,
+	/// Registry of the open connections
+	server: &'static Server,
+	/// Whether it stopped accepting connections to shut down
+	stopping: bool,
+	/// Time to accept again if accepting failed, since the listeners won't get another event for the connections
+	/// which are waiting
+	retry_accept: Option<Instant>,
+}
+
+
+/// A client's connection
+struct Connection
+{
+	stream: TcpStream,
+	/// Entry in the registry, which has the client's address and last activity
+	handle: ConnectionHandle,
+	/// Bytes of the requests which weren't handled yet
+	buffer: Box<[u8; REQUEST_SIZE]>,
+	/// Amount of bytes in the buffer
+	length: usize,
+	state: ConnectionState,
+	/// Last time bytes were read or written, which is also in the registry but this doesn't need its lock
+	last_active: Instant,
+}
+
+
+/// What a connection is doing
+enum ConnectionState
+{
+	/// Waiting for a complete request
+	Reading,
+	/// Waiting for the thread pool to make the response
+	Responding,
+	/// Writing the response
+	Writing(ResponseWriter),
+	/// Waiting for the thread pool to send part of a file, and whether the connection became writable meanwhile
+	Sending
+	{
+		is_woken: bool,
+	},
+}
+use ConnectionState::*;
+
+
+/// Work which the thread pool finished for a connection
+enum Finished
+{
+	/// A response to write
+	Response(ResponseWriter),
+	/// Part of a response which was sent, with the amount of bytes and whether the response is finished
+	Sent(ResponseWriter, u64, std::io::Result<bool>),
+}
+
+
+/// An event from `epoll_wait`, which is packed on x86-64
+#[derive(Clone, Copy)]
+#[cfg_attr(target_arch = "x86_64", repr(C, packed))]
+#[cfg_attr(not(target_arch = "x86_64"), repr(C))]
+struct EpollEvent
+{
+	events: u32,
+	data: u64,
+}
+
+
+impl<'a> Reactor<'a>
+{
+	/// Create with the connection registry and the thread pool which makes the responses
+	pub fn new(config: &'static Config, server: &'static Server, pool: &'a ThreadPool) -> std::io::Result<Reactor<'a>>
+	{
+		let epoll = unsafe { epoll_create1(CLOSE_ON_EXEC) };
+		if epoll < 0 {
+			return Err(std::io::Error::last_os_error());
+		}
+		let epoll = unsafe { OwnedFd::from_raw_fd(epoll) };
+
+		let waker = unsafe { eventfd(0, CLOSE_ON_EXEC | EVENT_FILE_NON_BLOCKING) };
+		if waker < 0 {
+			return Err(std::io::Error::last_os_error());
+		}
+		let waker = Arc::new(unsafe { File::from_raw_fd(waker) });
+
+		register(&epoll, waker.as_raw_fd(), READABLE | EDGE_TRIGGERED, WAKER_TOKEN)?;
+
+		let (sender, finished) = std::sync::mpsc::channel();
+
+		return Ok(Reactor {
+			epoll,
+			waker,
+			listeners: Vec::new(),
+			connections: HashMap::new(),
+			next_token: FIRST_LISTENER_TOKEN,
+			ready: Vec::new(),
+			finished,
+			sender,
+			pool,
+			config,
+			server,
+			stopping: false,
+			retry_accept: None,
+		});
+	}
+
+
+	/// Accept connections from the listeners, which become non-blocking, and handle events until the server
+	/// is shut down and the responses are finished, or until there's an error waiting for them
+	pub fn run(&mut self, listeners: Vec<TcpListener>) -> std::io::Result<()>
+	{
+		for listener in &listeners {
+			register(&self.epoll, listener.as_raw_fd(), READABLE | EDGE_TRIGGERED, self.next_token)?;
+			listener.set_nonblocking(true)?;
+			self.next_token += 1;
+		}
+		self.listeners = listeners;
+
+		let mut events = [EpollEvent { events: 0, data: 0 }; MAX_EVENTS];
+
+		loop {
+			// Don't wait if some connections can continue writing
+			let timeout = match self.ready.is_empty() {
+				true => WAIT_TIMEOUT,
+				false => 0,
+			};
+
+			let count = match unsafe { epoll_wait(self.epoll.as_raw_fd(), events.as_mut_ptr(), MAX_EVENTS as c_int, timeout) } {
+				count @ 0.. => count as usize,
+				_ => {
+					let error = std::io::Error::last_os_error();
+					match error.kind() {
+						// Check whether a signal asked to shut down
+						Interrupted => 0,
+						_ => return Err(error),
+					}
+				},
+			};
+
+			for event in &events[..count] {
+				let token = event.data;
+				match token {
+					WAKER_TOKEN => self.receive_finished(),
+					_ => match (token - FIRST_LISTENER_TOKEN) as usize {
+						i if i < self.listeners.len() => self.accept(i),
+						_ => self.advance(token),
+					},
+				}
+			}
+
+			for token in core::mem::take(&mut self.ready) {
+				self.advance(token);
+			}
+
+			if let Some(time) = self.retry_accept && time <= Instant::now() {
+				self.retry_accept = None;
+				for i in 0..self.listeners.len() {
+					self.accept(i);
+				}
+			}
+
+			// Stop listening, then stop when the responses are finished
+			if !self.stopping && is_shutting_down() {
+				self.stopping = true;
+				self.listeners.clear();
+				start_grace_timer(self.config.shutdown_timeout);
+			}
+			if self.stopping && self.connections.iter().all(|(_token, connection)| matches!(connection.state, Reading)) {
+				return Ok(());
+			}
+
+			self.close_idle();
+			self.server.print_if_requested();
+		}
+	}
+
+
+	/// Accept all of the incoming connections of a listener
+	fn accept(&mut self, listener_index: usize)
+	{
+		loop {
+			let (stream, address) = match self.listeners[listener_index].accept() {
+				Ok(accepted) => accepted,
+				Err(error) if error.kind() == WouldBlock => return,
+				Err(error) if error.kind() == Interrupted => continue,
+				// Try again soon, for example if there are too many files open
+				Err(error) => {
+					if self.config.log >= Log::Errors {
+						eprint(&format!("Failed to accept a connection - {error}\n"));
+					}
+					self.retry_accept = Some(Instant::now() + ACCEPT_RETRY_DELAY);
+					return;
+				},
+			};
+
+			if stream.set_nonblocking(true).is_err() {
+				continue;
+			}
+			let _ = stream.set_nodelay(true);
+
+			let handle = match self.server.add(address) {
+				Some(handle) => handle,
+				None => {
+					reject_connection(stream);
+					continue;
+				},
+			};
+
+			let token = self.next_token;
+			self.next_token += 1;
+
+			let events = READABLE | WRITABLE | PEER_CLOSED | EDGE_TRIGGERED;
+			if register(&self.epoll, stream.as_raw_fd(), events, token).is_err() {
+				continue;
+			}
+
+			self.connections.insert(token, Connection {
+				stream,
+				handle,
+				buffer: Box::new([0; REQUEST_SIZE]),
+				length: 0,
+				state: Reading,
+				last_active: Instant::now(),
+			});
+		}
+	}
+
+
+	/// Continue writing the responses which the thread pool made or sent part of
+	fn receive_finished(&mut self)
+	{
+		let mut count = [0; size_of::<u64>()];
+		let _ = (&*self.waker).read(&mut count);
+
+		while let Ok((token, finished)) = self.finished.try_recv() {
+			// Skip the response if the connection was closed
+			let connection = match self.connections.get_mut(&token) {
+				Some(connection) => connection,
+				None => continue,
+			};
+			match finished {
+				Finished::Response(writer) => connection.state = Writing(writer),
+				Finished::Sent(writer, size, result) => {
+					if size > 0 {
+						connection.last_active = Instant::now();
+						connection.handle.add_bytes_sent(size);
+					}
+
+					// Wait until the connection is writable again, unless it already became writable
+					let is_woken = matches!(connection.state, Sending { is_woken: true });
+					connection.state = Writing(writer);
+					match result {
+						Ok(_) => (),
+						Err(error) if error.kind() == WouldBlock && is_woken => (),
+						Err(error) if error.kind() == WouldBlock => continue,
+						Err(_) => {
+							self.connections.remove(&token);
+							continue;
+						},
+					}
+				},
+			}
+			self.advance(token);
+		}
+	}
+
+
+	/// Read, respond, or write as much as possible without blocking
+	fn advance(&mut self, token: u64)
+	{
+		let connection = match self.connections.get_mut(&token) {
+			Some(connection) => connection,
+			None => return,
+		};
+
+		loop {
+			match &mut connection.state {
+				Reading => {
+					// Have the thread pool respond to the next request
+					if let Some(head_end) = find_head_end(&connection.buffer[..connection.length]) {
+						let request = Request::parse(&connection.buffer[..head_end]);
+						connection.buffer.copy_within(head_end..connection.length, 0);
+						connection.length -= head_end;
+
+						match request {
+							None => connection.state = Writing(ResponseWriter::new(Response::new(BadRequest), true, false)),
+							Some(request) => {
+								connection.state = Responding;
+								connection.handle.set_path(&request.path);
+								let address = connection.handle.address;
+								self.respond(token, address, request);
+								return;
+							},
+						}
+						continue;
+					}
+
+					// Fail if the request is too big
+					if connection.length == REQUEST_SIZE {
+						connection.state = Writing(ResponseWriter::new(Response::new(BadRequest), true, false));
+						continue;
+					}
+
+					// Read more bytes, otherwise the client closed the connection
+					match connection.stream.read(&mut connection.buffer[connection.length..]) {
+						Ok(0) => break,
+						Ok(size) => {
+							connection.length += size;
+							connection.last_active = Instant::now();
+							connection.handle.touch();
+						},
+						Err(error) if error.kind() == WouldBlock => return,
+						Err(error) if error.kind() == Interrupted => (),
+						Err(_) => break,
+					}
+				},
+				Responding => return,
+				Sending { is_woken } => {
+					*is_woken = true;
+					return;
+				},
+				// Send the files with the thread pool, since reading them can block while the disk is busy
+				Writing(writer) if writer.is_at_file() => {
+					let stream = match connection.stream.try_clone() {
+						Ok(stream) => stream,
+						Err(_) => break,
+					};
+					let writer = match core::mem::replace(&mut connection.state, Sending { is_woken: false }) {
+						Writing(writer) => writer,
+						_ => unreachable!(),
+					};
+					self.send(token, writer, stream);
+					return;
+				},
+				Writing(writer) => {
+					let sent = writer.sent;
+					let result = writer.write_in_memory(&mut connection.stream, WRITE_LIMIT);
+					if writer.sent > sent {
+						connection.last_active = Instant::now();
+						connection.handle.add_bytes_sent(writer.sent - sent);
+					}
+
+					match result {
+						// Continue with the next request unless shutting down, since it could already be in the buffer
+						Ok(true) => match writer.keep_alive && !self.stopping {
+							true => connection.state = Reading,
+							false => break,
+						},
+						// Let the other connections have a turn
+						Ok(false) => {
+							self.ready.push(token);
+							return;
+						},
+						Err(error) if error.kind() == WouldBlock => return,
+						Err(_) => break,
+					}
+				},
+			}
+		}
+
+		// Close the connection
+		self.connections.remove(&token);
+	}
+
+
+	/// Make the response with the thread pool, then wake the loop to write it
+	fn respond(&self, token: u64, address: SocketAddr, request: Request)
+	{
+		let config = self.config;
+		let sender = self.sender.clone();
+		let waker = Arc::clone(&self.waker);
+
+		self.pool.execute(move || {
+			// Respond with an error and close the connection if making the response panics, so it isn't left waiting
+			let (response, is_ok) = match std::panic::catch_unwind(AssertUnwindSafe(|| respond(config, &request))) {
+				Ok(response) => (response, true),
+				Err(_) => (Response::new(InternalServerError), false),
+			};
+			log_response(config, address, &request, &response);
+			let keep_alive = request.keep_alive && is_ok && !is_shutting_down();
+			let writer = ResponseWriter::new(response, request.method != Method::Head, keep_alive);
+			if sender.send((token, Finished::Response(writer))).is_ok() {
+				let _ = (&*waker).write(&1u64.to_ne_bytes());
+			}
+		});
+	}
+
+
+	/// Send part of a response with the thread pool, then wake the loop to continue it
+	fn send(&self, token: u64, mut writer: ResponseWriter, mut stream: TcpStream)
+	{
+		let sender = self.sender.clone();
+		let waker = Arc::clone(&self.waker);
+
+		self.pool.execute(move || {
+			let sent = writer.sent;
+			let result = writer.write(&mut stream, WRITE_LIMIT);
+			let size = writer.sent - sent;
+			if sender.send((token, Finished::Sent(writer, size, result))).is_ok() {
+				let _ = (&*waker).write(&1u64.to_ne_bytes());
+			}
+		});
+	}
+
+
+	/// Close the connections which haven't sent a request or read any of the response for a while
+	fn close_idle(&mut self)
+	{
+		let timeout = self.config.idle_timeout;
+		self.connections.retain(|_token, connection| {
+			matches!(connection.state, Responding | Sending { .. }) || connection.last_active.elapsed() < timeout
+		});
+	}
+}
+
+
+/// Add a file descriptor to the event poll instance
+fn register(epoll: &OwnedFd, fd: RawFd, events: u32, token: u64) -> std::io::Result<()>
+{
+	let mut event = EpollEvent {
+		events,
+		data: token,
+	};
+	if unsafe { epoll_ctl(epoll.as_raw_fd(), ADD, fd, &mut event) } < 0 {
+		return Err(std::io::Error::last_os_error());
+	}
+	return Ok(());
+}
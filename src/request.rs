@@ -1,13 +1,62 @@
+use std::time::SystemTime;
+
+use crate::http_date::*;
+
+
 /// An HTTP request
 pub struct Request
 {
+	/// Kind of request
+	pub method: Method,
 	/// Full path
 	pub path: String,
 	/// Query parameters
 	pub query: Vec<QueryParam>,
-	/// Start position in a header like `Range: bytes=3702784-`
-	pub range_start: Option<usize>,
+	/// Ranges in a header like `Range: bytes=3702784-`, which is empty for the whole file
+	pub ranges: Vec<ByteRange>,
+	/// Whether the connection should stay open after the response
+	pub keep_alive: bool,
+	/// Entity tags in a header like `If-None-Match: "1a-2b", W/"3c"`, which can also be `*`
+	pub if_none_match: Vec<String>,
+	/// Time in a header like `If-Modified-Since: Sun, 06 Nov 1994 08:49:37 GMT`
+	pub if_modified_since: Option<SystemTime>,
+	/// Entity tag or time in an `If-Range` header, which only allows the ranges if it's current
+	pub if_range: Option<IfRange>,
+}
+
+
+/// The validator in an `If-Range` header
+pub enum IfRange
+{
+	ETag(String),
+	Date(SystemTime),
+}
+
+
+/// The kind of HTTP request
+#[derive(Clone, Copy, PartialEq)]
+pub enum Method
+{
+	Get,
+	Head,
+	Options,
+	/// Any other method, which isn't allowed
+	Other,
+}
+
+
+/// A range of bytes in a file from a `Range` header
+#[derive(Clone, Copy)]
+pub enum ByteRange
+{
+	/// `BEGIN-` which is the rest of the file
+	From(u64),
+	/// `BEGIN-END` where the end is inclusive
+	FromTo(u64, u64),
+	/// `-LENGTH` which is the end of the file
+	Suffix(u64),
 }
+use ByteRange::*;
 
 
 /// An optional part after the question mark of a URL
@@ -36,12 +85,19 @@ impl Request
 	{
 		let mut result = Request::new();
 
-		// Parse a GET method or fail
-		const GET: &[u8] = b"GET ";
-		if !request.starts_with(GET) {
-			return None;
-		}
-		let mut i = GET.len();
+		// Parse the method or fail
+		let method_length = match request.iter().position(|&byte| byte == b' ') {
+			Some(method_length @ 1..) => method_length,
+			_ => return None,
+		};
+		result.method = match &request[..method_length] {
+			b"GET" => Method::Get,
+			b"HEAD" => Method::Head,
+			b"OPTIONS" => Method::Options,
+			method if method.iter().all(u8::is_ascii_uppercase) => Method::Other,
+			_ => return None,
+		};
+		let mut i = method_length + 1;
 
 		// Parse the URL
 		let mut part_kind = Path;
@@ -50,16 +106,11 @@ impl Request
 		while i < request.len() {
 			let byte = request[i];
 			match byte {
-				// Finish if there's a space
+				// Finish the URL if there's a space
 				b' ' => {
 					part.extend(&request[part_start..i]);
 					result.insert_part(part_kind, &part);
-					if result.path.ends_with(".mp4") {
-						break;
-					}
-					else {
-						return Some(result);
-					}
+					break;
 				},
 				// Percent encoding
 				b'%' => {
@@ -120,50 +171,63 @@ impl Request
 			i += 1;
 		}
 
-		// Parse the range start
-		const SUBSLICE: &[u8] = b"\r\nRange: bytes=";
+		// Parse the version or fail
 		let request = &request[i..];
-		let mut found = false;
-		let mut subslice_i = 0;
-		let mut begin = 0;
-		let mut end = 0;
-		for (slice_i, &c) in request.iter().enumerate() {
-			// Parse the subslice
-			if !found {
-				if c != SUBSLICE[subslice_i] {
-					subslice_i = 0;
-				}
-				else if subslice_i == SUBSLICE.len() - 1 {
-					found = true;
-				}
-				else {
-					subslice_i += 1;
+		let mut lines = request.split(|&byte| byte == b'\n');
+		result.keep_alive = match lines.next() {
+			Some(b" HTTP/1.1\r") => true,
+			Some(b" HTTP/1.0\r") => false,
+			_ => return None,
+		};
+
+		// Parse the headers
+		let mut has_body = false;
+		for line in lines {
+			let (name, value) = match split_header(line) {
+				Some(header) => header,
+				None => continue,
+			};
+
+			if name.eq_ignore_ascii_case(b"Connection") {
+				for token in value.split(|&byte| byte == b',') {
+					let token = token.trim_ascii();
+					if token.eq_ignore_ascii_case(b"close") {
+						result.keep_alive = false;
+					}
+					else if token.eq_ignore_ascii_case(b"keep-alive") {
+						result.keep_alive = true;
+					}
 				}
 			}
-			// Parse the number
-			else {
-				match (begin, c) {
-					(0, b'0'..=b'9') => begin = slice_i,
-					(_, b'0'..=b'9') => (),
-					(0, _) => return None,
-					(_, _) => {
-						end = slice_i;
-						break;
-					},
+			else if name.eq_ignore_ascii_case(b"Content-Length") || name.eq_ignore_ascii_case(b"Transfer-Encoding") {
+				// Request bodies aren't read, so the next request can't be found
+				if value != b"0" {
+					has_body = true;
 				}
 			}
+			else if name.eq_ignore_ascii_case(b"Range") {
+				result.ranges = parse_ranges(value).unwrap_or_default();
+			}
+			else if name.eq_ignore_ascii_case(b"If-None-Match") {
+				result.if_none_match = value.split(|&byte| byte == b',')
+					.filter_map(|tag| core::str::from_utf8(tag.trim_ascii()).ok())
+					.filter(|tag| !tag.is_empty())
+					.map(String::from)
+					.collect();
+			}
+			else if name.eq_ignore_ascii_case(b"If-Modified-Since") {
+				result.if_modified_since = parse_http_date(value);
+			}
+			else if name.eq_ignore_ascii_case(b"If-Range") {
+				result.if_range = match value.ends_with(b"\"") {
+					true => core::str::from_utf8(value).ok().map(|tag| IfRange::ETag(tag.to_string())),
+					false => parse_http_date(value).map(IfRange::Date),
+				};
+			}
 		}
-		if !found {
-			return Some(result);
+		if has_body {
+			result.keep_alive = false;
 		}
-		let number_str = match core::str::from_utf8(&request[begin..end]) {
-			Ok(number_str) => number_str,
-			_ => return Some(result),
-		};
-		result.range_start = match usize::from_str_radix(number_str, 10) {
-			Ok(number) => Some(number),
-			_ => return Some(result),
-		};
 
 		return Some(result);
 	}
@@ -200,9 +264,216 @@ impl Request
 	fn new() -> Request
 	{
 		return Request {
+			method: Method::Get,
 			path: String::new(),
 			query: vec![],
-			range_start: None,
+			ranges: Vec::new(),
+			keep_alive: false,
+			if_none_match: Vec::new(),
+			if_modified_since: None,
+			if_range: None,
+		};
+	}
+}
+
+
+impl Method
+{
+	/// Get the name like `"GET"`
+	pub fn to_str(self) -> &'static str
+	{
+		return match self {
+			Method::Get => "GET",
+			Method::Head => "HEAD",
+			Method::Options => "OPTIONS",
+			Method::Other => "OTHER",
+		};
+	}
+}
+
+
+impl ByteRange
+{
+	/// Get the inclusive begin and end positions in a file of the size,
+	/// otherwise None if the range isn't satisfiable
+	pub fn resolve(self, total_size: u64) -> Option<(u64, u64)>
+	{
+		let last = total_size.checked_sub(1)?;
+		return match self {
+			From(begin) if begin <= last => Some((begin, last)),
+			FromTo(begin, end) if begin <= last => Some((begin, end.min(last))),
+			Suffix(length) if length > 0 => Some((total_size - length.min(total_size), last)),
+			_ => None,
+		};
+	}
+}
+
+
+/// Get the inclusive begin and end positions of the satisfiable ranges in a file of the size, in order,
+/// where the ones which overlap or touch are merged so the same bytes aren't sent more than once
+pub fn resolve_ranges(ranges: &[ByteRange], total_size: u64) -> Vec<(u64, u64)>
+{
+	let mut resolved: Vec<(u64, u64)> = ranges.iter()
+		.filter_map(|range| range.resolve(total_size))
+		.collect();
+	resolved.sort_unstable();
+
+	let mut result: Vec<(u64, u64)> = Vec::with_capacity(resolved.len());
+	for (begin, end) in resolved {
+		match result.last_mut() {
+			Some(last) if begin <= last.1 + 1 => last.1 = last.1.max(end),
+			_ => result.push((begin, end)),
+		}
+	}
+	return result;
+}
+
+
+/// Split a header line like `Range: bytes=0-\r` into the trimmed name and value
+fn split_header(line: &[u8]) -> Option<(&[u8], &[u8])>
+{
+	let colon = line.iter().position(|&byte| byte == b':')?;
+	return Some((line[..colon].trim_ascii(), line[colon+1..].trim_ascii()));
+}
+
+
+/// Get the ranges from a header value like `bytes=0-499, -500`, otherwise None
+/// if any of them are invalid so that the header is ignored
+fn parse_ranges(value: &[u8]) -> Option<Vec<ByteRange>>
+{
+	// Limit the amount of ranges since each one is a separate part of the response
+	const MAX_RANGES: usize = 16;
+
+	let value = value.strip_prefix(b"bytes=")?;
+
+	let mut result = Vec::new();
+	for spec in value.split(|&byte| byte == b',') {
+		let spec = spec.trim_ascii();
+		let dash = spec.iter().position(|&byte| byte == b'-')?;
+		let range = match (parse_position(&spec[..dash]), parse_position(&spec[dash+1..])) {
+			(Some(begin), None) if dash + 1 == spec.len() => From(begin),
+			(Some(begin), Some(end)) if begin <= end => FromTo(begin, end),
+			(None, Some(length)) if dash == 0 => Suffix(length),
+			_ => return None,
 		};
+		result.push(range);
+	}
+
+	if result.len() > MAX_RANGES {
+		return None;
+	}
+
+	return Some(result);
+}
+
+
+/// Parse a position in a range which only has digits
+fn parse_position(digits: &[u8]) -> Option<u64>
+{
+	if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+		return None;
+	}
+	return core::str::from_utf8(digits).ok()?.parse().ok();
+}
+
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+
+	/// Parse a `Range` header value and resolve each range in a file of the size
+	fn resolve(value: &str, total_size: u64) -> Option<Vec<Option<(u64, u64)>>>
+	{
+		let ranges = parse_ranges(value.as_bytes())?;
+		return Some(ranges.into_iter().map(|range| range.resolve(total_size)).collect());
+	}
+
+
+	#[test]
+	fn explicit_range()
+	{
+		assert_eq!(resolve("bytes=0-499", 1000), Some(vec![Some((0, 499))]));
+		assert_eq!(resolve("bytes=500-500", 1000), Some(vec![Some((500, 500))]));
+		assert_eq!(resolve("bytes=900-2000", 1000), Some(vec![Some((900, 999))]));
+	}
+
+
+	#[test]
+	fn suffix_range()
+	{
+		assert_eq!(resolve("bytes=-500", 1000), Some(vec![Some((500, 999))]));
+		assert_eq!(resolve("bytes=-5000", 1000), Some(vec![Some((0, 999))]));
+		assert_eq!(resolve("bytes=-0", 1000), Some(vec![None]));
+	}
+
+
+	#[test]
+	fn open_ended_range()
+	{
+		assert_eq!(resolve("bytes=0-", 1000), Some(vec![Some((0, 999))]));
+		assert_eq!(resolve("bytes=999-", 1000), Some(vec![Some((999, 999))]));
+	}
+
+
+	#[test]
+	fn unsatisfiable_ranges()
+	{
+		assert_eq!(resolve("bytes=1000-", 1000), Some(vec![None]));
+		assert_eq!(resolve("bytes=1000-1999", 1000), Some(vec![None]));
+		assert_eq!(resolve("bytes=0-", 0), Some(vec![None]));
+		assert_eq!(resolve("bytes=-1", 0), Some(vec![None]));
+	}
+
+
+	#[test]
+	fn multiple_ranges()
+	{
+		assert_eq!(resolve("bytes=0-0, -1", 1000), Some(vec![Some((0, 0)), Some((999, 999))]));
+		assert_eq!(resolve("bytes=0-1,2-3", 1000), Some(vec![Some((0, 1)), Some((2, 3))]));
+	}
+
+
+	#[test]
+	fn over_limit_ranges()
+	{
+		let ranges = vec!["0-0"; 16].join(",");
+		assert_eq!(resolve(&format!("bytes={ranges}"), 1000).map(|ranges| ranges.len()), Some(16));
+		let ranges = vec!["0-0"; 17].join(",");
+		assert_eq!(resolve(&format!("bytes={ranges}"), 1000), None);
+	}
+
+
+	#[test]
+	fn malformed_ranges()
+	{
+		for value in ["", "bytes=", "bytes=-", "bytes=5-1", "bytes=a-b", "bytes=1-2-3", "bytes=+1-2", "items=0-1",
+			"bytes=0-1,", "bytes=99999999999999999999-"] {
+			assert_eq!(resolve(value, 1000), None, "{value}");
+		}
+	}
+
+
+	#[test]
+	fn merged_ranges()
+	{
+		let parse = |value: &str, total_size| resolve_ranges(&parse_ranges(value.as_bytes()).unwrap(), total_size);
+		assert_eq!(parse(&format!("bytes={}", vec!["0-"; 16].join(",")), 1000), vec![(0, 999)]);
+		assert_eq!(parse("bytes=10-20,0-5,6-9", 1000), vec![(0, 20)]);
+		assert_eq!(parse("bytes=0-5,7-9", 1000), vec![(0, 5), (7, 9)]);
+		assert_eq!(parse("bytes=-100,0-10,950-", 1000), vec![(0, 10), (900, 999)]);
+		assert_eq!(parse("bytes=2000-,0-0", 1000), vec![(0, 0)]);
+	}
+
+
+	#[test]
+	fn range_header()
+	{
+		let request = Request::parse(b"GET /Movie.mp4 HTTP/1.1\r\nRange: bytes=0-99, -10\r\n\r\n").unwrap();
+		assert_eq!(resolve_ranges(&request.ranges, 1000), vec![(0, 99), (990, 999)]);
+
+		let request = Request::parse(b"GET /Movie.mp4 HTTP/1.1\r\nRange: bytes=9-1\r\n\r\n").unwrap();
+		assert!(request.ranges.is_empty());
 	}
 }
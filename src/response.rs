@@ -0,0 +1,368 @@
+use std::fs::File;
+use std::io::ErrorKind::Interrupted;
+use std::io::ErrorKind::Unsupported;
+use std::io::ErrorKind::WriteZero;
+use std::io::IoSlice;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::net::TcpStream;
+use std::sync::Arc;
+
+use crate::status::*;
+
+use Status::*;
+
+
+// Size in bytes of a portion of a video (don't exceed this)
+const VIDEO_BUFFER_SIZE: usize = 1 << 21;
+
+
+/// An HTTP response which is built before anything is written
+pub struct Response
+{
+	/// Status code
+	pub status: Status,
+	/// Header lines, each ending with a line ending
+	pub headers: String,
+	/// Content after the headers
+	pub body: Vec<Part>,
+}
+
+
+/// A portion of the content of a response
+pub enum Part
+{
+	/// Content built into the executable
+	Static(&'static [u8]),
+	/// Content in memory
+	Bytes(Vec<u8>),
+	/// Content in memory which other responses can also have
+	Shared
+	{
+		bytes: Arc<[u8]>,
+		begin: usize,
+		length: usize,
+	},
+	/// Content read from a file when written
+	File
+	{
+		file: Arc<File>,
+		begin: u64,
+		length: u64,
+	},
+}
+
+
+/// Writes a response bit by bit, so a non-blocking socket can continue when it's ready
+pub struct ResponseWriter
+{
+	/// The head, then the content unless it's only the headers for a HEAD request
+	parts: Vec<Part>,
+	/// Index of the part being written
+	index: usize,
+	/// Amount of the part which was written
+	offset: u64,
+	/// Bytes read from a file which weren't written yet, if the file can't be sent directly
+	buffer: Vec<u8>,
+	/// Amount of the buffer which was written
+	buffer_offset: usize,
+	/// Whether files can be sent without copying them into this process
+	can_send_file: bool,
+	/// Whether the connection stays open afterwards
+	pub keep_alive: bool,
+	/// Amount of bytes which were written
+	pub sent: u64,
+}
+
+
+impl Response
+{
+	/// Create without headers or content
+	pub fn new(status: Status) -> Response
+	{
+		return Response {
+			status,
+			headers: String::new(),
+			body: Vec::new(),
+		};
+	}
+
+
+	/// Add a header line
+	pub fn add_header(&mut self, name: &str, value: &str)
+	{
+		self.headers += name;
+		self.headers += ": ";
+		self.headers += value;
+		self.headers += "\r\n";
+	}
+
+
+	/// Get the size in bytes of the content
+	pub fn content_length(&self) -> u64
+	{
+		return self.body.iter().map(Part::len).sum();
+	}
+}
+
+
+impl Part
+{
+	/// Get the size in bytes
+	pub fn len(&self) -> u64
+	{
+		return match self {
+			Part::Static(bytes) => bytes.len() as u64,
+			Part::Bytes(bytes) => bytes.len() as u64,
+			Part::Shared { length, .. } => *length as u64,
+			Part::File { length, .. } => *length,
+		};
+	}
+
+
+	/// Get a portion, which copies it if it's in memory and isn't static or shared
+	pub fn slice(&self, begin: u64, length: u64) -> Part
+	{
+		let range = begin as usize .. (begin + length) as usize;
+		return match self {
+			Part::Static(bytes) => Part::Static(&bytes[range]),
+			Part::Bytes(bytes) => Part::Bytes(bytes[range].to_vec()),
+			Part::Shared { bytes, begin: shared_begin, .. } => Part::Shared {
+				bytes: Arc::clone(bytes),
+				begin: shared_begin + range.start,
+				length: length as usize,
+			},
+			Part::File { file, begin: file_begin, .. } => Part::File {
+				file: Arc::clone(file),
+				begin: file_begin + begin,
+				length,
+			},
+		};
+	}
+
+
+	/// Get the content if it's in memory
+	fn bytes(&self) -> Option<&[u8]>
+	{
+		return match self {
+			Part::Static(bytes) => Some(bytes),
+			Part::Bytes(bytes) => Some(bytes),
+			Part::Shared { bytes, begin, length } => Some(&bytes[*begin..begin + length]),
+			Part::File { .. } => None,
+		};
+	}
+}
+
+
+impl ResponseWriter
+{
+	/// Create from a response, where the content is skipped for a HEAD request
+	pub fn new(response: Response, send_body: bool, keep_alive: bool) -> ResponseWriter
+	{
+		// Every response has a length except for ones which can't have content
+		let content_length = match &response.status {
+			NotModified | NoContent => String::new(),
+			_ => format!("Content-Length: {}\r\n", response.content_length()),
+		};
+
+		let connection = match keep_alive {
+			true => "Connection: keep-alive\r\n\r\n",
+			false => "Connection: close\r\n\r\n",
+		};
+
+		let head = format!("HTTP/1.1 {}\r\n{}{content_length}{connection}", response.status.to_str(), response.headers);
+
+		let mut parts = vec![Part::Bytes(head.into_bytes())];
+		if send_body {
+			parts.extend(response.body);
+		}
+
+		return ResponseWriter {
+			parts,
+			index: 0,
+			offset: 0,
+			buffer: Vec::new(),
+			buffer_offset: 0,
+			can_send_file: cfg!(target_os = "linux"),
+			keep_alive,
+			sent: 0,
+		};
+	}
+
+
+	/// Write until it's finished or the limit in bytes is reached, returning whether it's finished,
+	/// where a non-blocking socket which isn't ready is an error with `WouldBlock`
+	pub fn write(&mut self, stream: &mut TcpStream, limit: u64) -> std::io::Result<bool>
+	{
+		return self.write_parts(stream, limit, true);
+	}
+
+
+	/// Write like `write` but stop before the next part from a file, since reading it can block
+	pub fn write_in_memory(&mut self, stream: &mut TcpStream, limit: u64) -> std::io::Result<bool>
+	{
+		return self.write_parts(stream, limit, false);
+	}
+
+
+	/// Whether the next bytes to write are from a file
+	pub fn is_at_file(&self) -> bool
+	{
+		let mut offset = self.offset;
+		for part in &self.parts[self.index..] {
+			if part.len() > offset {
+				return matches!(part, Part::File { .. });
+			}
+			offset = 0;
+		}
+		return false;
+	}
+
+
+	/// Write until it's finished, the limit in bytes is reached, or the next part is from a file if they're not included
+	fn write_parts(&mut self, stream: &mut TcpStream, limit: u64, include_files: bool) -> std::io::Result<bool>
+	{
+		let mut total_size: u64 = 0;
+
+		while self.index < self.parts.len() && total_size < limit {
+			// Skip the part if it's done
+			let part = &self.parts[self.index];
+			if self.offset >= part.len() {
+				self.index += 1;
+				self.offset = 0;
+				continue;
+			}
+			if !include_files && matches!(part, Part::File { .. }) {
+				break;
+			}
+
+			let is_buffered = matches!(part, Part::File { .. }) && !self.can_send_file;
+
+			let result = match part {
+				// Send from the page cache if possible, otherwise read and write the rest
+				Part::File { file, begin, length } => {
+					let position = begin + self.offset;
+					let remaining = length - self.offset;
+					match is_buffered {
+						false => match send_file(stream, file, position, remaining) {
+							Err(error) if error.kind() == Unsupported => {
+								self.can_send_file = false;
+								continue;
+							},
+							result => result,
+						},
+						true => {
+							// Read the next portion of the file if the last one was written
+							if self.buffer_offset == self.buffer.len() {
+								let size = remaining.min(VIDEO_BUFFER_SIZE as u64) as usize;
+								self.buffer.resize(size, 0);
+								self.buffer_offset = 0;
+								let mut file = &**file;
+								file.seek(SeekFrom::Start(position))?;
+								file.read_exact(&mut self.buffer)?;
+							}
+							stream.write(&self.buffer[self.buffer_offset..])
+						},
+					}
+				},
+				// Write this and the following parts in memory together
+				_ => {
+					let slices: Vec<IoSlice> = self.parts[self.index..].iter()
+						.map_while(Part::bytes)
+						.enumerate()
+						.map(|(i, bytes)| match i {
+							0 => IoSlice::new(&bytes[self.offset as usize..]),
+							_ => IoSlice::new(bytes),
+						})
+						.collect();
+					stream.write_vectored(&slices)
+				},
+			};
+
+			let size = match result {
+				Ok(0) => return Err(WriteZero.into()),
+				Ok(size) => size,
+				Err(error) if error.kind() == Interrupted => continue,
+				Err(error) => return Err(error),
+			};
+			total_size += size as u64;
+			self.sent += size as u64;
+
+			if is_buffered {
+				self.buffer_offset += size;
+			}
+			self.advance(size as u64);
+		}
+
+		return Ok(self.index >= self.parts.len());
+	}
+
+
+	/// Move past the bytes which were written, which can be in multiple parts
+	fn advance(&mut self, mut size: u64)
+	{
+		while size > 0 && self.index < self.parts.len() {
+			let remaining = self.parts[self.index].len() - self.offset;
+			if size < remaining {
+				self.offset += size;
+				return;
+			}
+			size -= remaining;
+			self.index += 1;
+			self.offset = 0;
+		}
+	}
+}
+
+
+/// Send part of a file straight to the socket without copying it into this process,
+/// where it's an error with `Unsupported` if the file can't be sent this way
+#[cfg(target_os = "linux")]
+fn send_file(stream: &TcpStream, file: &File, position: u64, count: u64) -> std::io::Result<usize>
+{
+	use core::ffi::c_int;
+	use std::io::ErrorKind::UnexpectedEof;
+	use std::os::fd::AsRawFd;
+
+	unsafe extern "C"
+	{
+		// https://man7.org/linux/man-pages/man2/sendfile.2.html
+		fn sendfile64(out_fd: c_int, in_fd: c_int, offset: *mut i64, count: usize) -> isize;
+	}
+
+	/// Maximum amount that `sendfile` transfers at once
+	const MAX_COUNT: u64 = 0x7ffff000;
+	/// Invalid argument, which happens if the file can't be memory mapped
+	const EINVAL: i32 = 22;
+	/// Function not implemented
+	const ENOSYS: i32 = 38;
+
+	let mut offset = match i64::try_from(position) {
+		Ok(offset) => offset,
+		Err(_) => return Err(Unsupported.into()),
+	};
+
+	let result = unsafe { sendfile64(stream.as_raw_fd(), file.as_raw_fd(), &mut offset, count.min(MAX_COUNT) as usize) };
+	return match result {
+		// The file ended early
+		0 => Err(UnexpectedEof.into()),
+		1.. => Ok(result as usize),
+		_ => {
+			let error = std::io::Error::last_os_error();
+			match error.raw_os_error() {
+				Some(EINVAL | ENOSYS) => Err(Unsupported.into()),
+				_ => Err(error),
+			}
+		},
+	};
+}
+
+
+/// Fail on systems without `sendfile`, so the file is read and written instead
+#[cfg(not(target_os = "linux"))]
+fn send_file(_stream: &TcpStream, _file: &File, _position: u64, _count: u64) -> std::io::Result<usize>
+{
+	return Err(Unsupported.into());
+}
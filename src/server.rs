@@ -1,17 +1,209 @@
-use std::net::TcpStream;
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::Ordering::SeqCst;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::time::Instant;
 
+use crate::print::*;
 
-/// All of the TCP streams
+
+/// Whether a signal asked to print the connections
+static INSPECT_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+
+/// Registry of the open connections
 pub struct Server
 {
-	streams: Vec<TcpStream>,
+	/// Connections by ID
+	connections: Mutex<HashMap<u64, ConnectionInfo>>,
+	/// ID for the next connection
+	next_id: AtomicU64,
+	/// Maximum amount of open connections, where 0 is unlimited
+	max_connections: usize,
+}
+
+
+/// What a connection is doing
+#[derive(Clone)]
+pub struct ConnectionInfo
+{
+	/// Client's address
+	pub address: SocketAddr,
+	/// Path of the last request, which is empty until the first one
+	pub path: String,
+	/// Bytes of the responses which were written
+	pub bytes_sent: u64,
+	/// Time the connection was accepted
+	pub start: Instant,
+	/// Last time bytes were read or written
+	pub last_active: Instant,
+}
+
+
+/// A connection in the registry, which is removed when this is dropped
+pub struct ConnectionHandle
+{
+	server: &'static Server,
+	id: u64,
+	/// Client's address
+	pub address: SocketAddr,
 }
 
 
 impl Server
 {
-	pub fn new(&self) -> Server
+	/// Create without connections, where a maximum of 0 is unlimited
+	pub fn new(max_connections: usize) -> Server
 	{
+		return Server {
+			connections: Mutex::new(HashMap::new()),
+			next_id: 0.into(),
+			max_connections,
+		};
+	}
 
+
+	/// Add a connection unless there are too many
+	pub fn add(&'static self, address: SocketAddr) -> Option<ConnectionHandle>
+	{
+		let mut connections = self.lock();
+		if self.max_connections != 0 && connections.len() >= self.max_connections {
+			return None;
+		}
+
+		let id = self.next_id.fetch_add(1, SeqCst);
+		let now = Instant::now();
+		connections.insert(id, ConnectionInfo {
+			address,
+			path: String::new(),
+			bytes_sent: 0,
+			start: now,
+			last_active: now,
+		});
+
+		return Some(ConnectionHandle {
+			server: self,
+			id,
+			address,
+		});
 	}
+
+
+	/// Get a copy of the connections, sorted from the oldest to the newest
+	pub fn list(&self) -> Vec<ConnectionInfo>
+	{
+		let mut result: Vec<ConnectionInfo> = self.lock().values().cloned().collect();
+		result.sort_by_key(|info| info.start);
+		return result;
+	}
+
+
+	/// Print the connections if a signal asked to
+	pub fn print_if_requested(&self)
+	{
+		if !INSPECT_REQUESTED.swap(false, SeqCst) {
+			return;
+		}
+
+		let connections = self.list();
+		let mut text = format!("{} connections\n", connections.len());
+		for info in connections {
+			let path = match info.path.is_empty() {
+				true => "-",
+				false => info.path.as_str(),
+			};
+			text += &format!("{} connected {}s ago, active {}s ago, sent {} bytes, {path}\n",
+				SocketAddr::new(info.address.ip().to_canonical(), info.address.port()),
+				info.start.elapsed().as_secs(), info.last_active.elapsed().as_secs(), info.bytes_sent);
+		}
+		print(&text);
+	}
+
+
+	/// Change a connection
+	fn update(&self, id: u64, change: impl FnOnce(&mut ConnectionInfo))
+	{
+		if let Some(info) = self.lock().get_mut(&id) {
+			change(info);
+			info.last_active = Instant::now();
+		}
+	}
+
+
+	/// Lock the connections even if another thread panicked with them
+	fn lock(&self) -> MutexGuard<'_, HashMap<u64, ConnectionInfo>>
+	{
+		return self.connections.lock().unwrap_or_else(|error| error.into_inner());
+	}
+}
+
+
+impl ConnectionHandle
+{
+	/// Record that bytes were read
+	pub fn touch(&self)
+	{
+		self.server.update(self.id, |_info| ());
+	}
+
+
+	/// Record the path of a new request
+	pub fn set_path(&self, path: &str)
+	{
+		self.server.update(self.id, |info| {
+			info.path.clear();
+			info.path += path;
+		});
+	}
+
+
+	/// Record that bytes of a response were written
+	pub fn add_bytes_sent(&self, size: u64)
+	{
+		self.server.update(self.id, |info| info.bytes_sent += size);
+	}
+}
+
+
+impl Drop for ConnectionHandle
+{
+	/// Remove the connection from the registry
+	fn drop(&mut self)
+	{
+		self.server.lock().remove(&self.id);
+	}
+}
+
+
+/// Print the connections when the process gets `SIGUSR1`
+#[cfg(target_os = "linux")]
+pub fn handle_inspect_signal()
+{
+	use core::ffi::c_int;
+
+	unsafe extern "C"
+	{
+		// https://man7.org/linux/man-pages/man2/signal.2.html
+		fn signal(signum: c_int, handler: usize) -> usize;
+	}
+
+	/// User-defined signal 1 (`SIGUSR1`)
+	const SIGUSR1: c_int = 10;
+
+	extern "C" fn handle_signal(_signal: c_int)
+	{
+		INSPECT_REQUESTED.store(true, SeqCst);
+	}
+
+	unsafe { signal(SIGUSR1, handle_signal as *const () as usize); }
+}
+
+
+/// Do nothing on systems without `SIGUSR1`
+#[cfg(not(target_os = "linux"))]
+pub fn handle_inspect_signal()
+{
 }
@@ -0,0 +1,64 @@
+use core::ffi::c_int;
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::Ordering::SeqCst;
+use core::time::Duration;
+
+use crate::ExitCode;
+
+
+/// Interrupt from the terminal (`SIGINT`)
+const SIGINT: c_int = 2;
+/// Termination request from a service manager (`SIGTERM`)
+const SIGTERM: c_int = 15;
+
+
+/// Whether a signal asked the server to stop
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+/// Whether the grace timer started
+static TIMER_STARTED: AtomicBool = AtomicBool::new(false);
+
+
+/// Stop the server gracefully on the first interrupt or termination signal, and immediately on the second one
+pub fn handle_shutdown_signals()
+{
+	unsafe extern "C"
+	{
+		// https://man7.org/linux/man-pages/man2/signal.2.html
+		fn signal(signum: c_int, handler: usize) -> usize;
+	}
+
+	unsafe {
+		signal(SIGINT, handle_signal as *const () as usize);
+		signal(SIGTERM, handle_signal as *const () as usize);
+	}
+}
+
+
+/// Whether the server should stop accepting connections and finish the responses
+pub fn is_shutting_down() -> bool
+{
+	return REQUESTED.load(SeqCst);
+}
+
+
+/// Exit after the grace time even if some responses aren't finished, which only starts once
+pub fn start_grace_timer(grace_time: Duration)
+{
+	if TIMER_STARTED.swap(true, SeqCst) {
+		return;
+	}
+
+	std::thread::spawn(move || {
+		std::thread::sleep(grace_time);
+		std::process::exit(ExitCode::Success as i32);
+	});
+}
+
+
+/// Ask the server to stop, or exit immediately if it was already asked
+extern "C" fn handle_signal(_signal: c_int)
+{
+	if REQUESTED.swap(true, SeqCst) {
+		std::process::exit(ExitCode::Success as i32);
+	}
+}
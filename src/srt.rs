@@ -0,0 +1,116 @@
+use crate::webvtt::*;
+
+
+/// Convert SubRip subtitles to WebVTT, where the cues without valid times are skipped
+/// https://en.wikipedia.org/wiki/SubRip
+pub fn srt_to_webvtt(text: &str) -> String
+{
+	let text = text.strip_prefix('\u{feff}').unwrap_or(text).replace("\r\n", "\n").replace('\r', "\n");
+
+	let mut cues = Vec::new();
+	for block in text.split("\n\n") {
+		// Skip the number before the times, which is optional
+		let mut lines = block.trim_matches('\n').lines().skip_while(|line| !line.contains("-->"));
+		let (start, end) = match lines.next().and_then(parse_times) {
+			Some(times) => times,
+			None => continue,
+		};
+
+		// Move the cue to the top if it's positioned at the top like `{\an8}`
+		let lines: Vec<&str> = lines.collect();
+		let settings = match lines.iter().any(|line| ["{\\an7}", "{\\an8}", "{\\an9}"].iter().any(|tag| line.contains(tag))) {
+			true => String::from("line:0"),
+			false => String::new(),
+		};
+
+		let text: Vec<String> = lines.iter()
+			.map(|line| convert_tags(line.trim_end()))
+			.filter(|line| !line.is_empty())
+			.collect();
+		add_cue(&mut cues, Cue {
+			start,
+			end,
+			settings,
+			text: text.join("\n"),
+		});
+	}
+
+	return make_webvtt(&cues);
+}
+
+
+/// Parse a line with the start and end times like `00:01:02,500 --> 00:01:04,000`, where the coordinates after them
+/// are ignored
+fn parse_times(line: &str) -> Option<(f64, f64)>
+{
+	let (start, end) = line.split_once("-->")?;
+	let end = end.split_whitespace().next()?;
+	return Some((parse_timestamp(start.trim())?, parse_timestamp(end)?));
+}
+
+
+/// Parse a timestamp like `01:02:03,456` in seconds, where the fraction can be after a period instead of a comma
+/// and the hours can be missing
+fn parse_timestamp(timestamp: &str) -> Option<f64>
+{
+	let timestamp = timestamp.replace(',', ".");
+	let mut result = 0.0;
+	let mut count = 0;
+	for part in timestamp.split(':') {
+		if part.is_empty() || !part.bytes().all(|byte| byte.is_ascii_digit() || byte == b'.') {
+			return None;
+		}
+		result = result * 60.0 + part.parse::<f64>().ok()?;
+		count += 1;
+	}
+	return match count {
+		2 | 3 => Some(result),
+		_ => None,
+	};
+}
+
+
+/// Escape a line of text for WebVTT, keeping the italic, bold, and underline tags and removing the others
+/// like `<font color="red">` and `{\an8}`
+fn convert_tags(line: &str) -> String
+{
+	let mut result = String::new();
+	let mut rest = line;
+
+	while let Some(c) = rest.chars().next() {
+		// Keep the basic tags in lowercase and remove the others, like `<I>` is `<i>` and `<font>` is removed
+		if c == '<' && let Some(end) = rest.find('>') {
+			let tag = rest[1..end].trim().to_ascii_lowercase();
+			let name = tag.trim_start_matches('/').split_whitespace().next().unwrap_or_default();
+			if matches!(name, "i" | "b" | "u") {
+				let slash = match tag.starts_with('/') {
+					true => "/",
+					false => "",
+				};
+				result += &format!("<{slash}{name}>");
+				rest = &rest[end + 1..];
+				continue;
+			}
+			if name == "font" {
+				rest = &rest[end + 1..];
+				continue;
+			}
+		}
+
+		// Remove the override tags of SubStation Alpha like `{\an8}`
+		if rest.starts_with("{\\") && let Some(end) = rest.find('}') {
+			rest = &rest[end + 1..];
+			continue;
+		}
+
+		match c {
+			'&' => result += "&amp;",
+			'<' => result += "&lt;",
+			'>' => result += "&gt;",
+			_ => result.push(c),
+		}
+		rest = &rest[c.len_utf8()..];
+	}
+
+	return result.trim().to_string();
+}
@@ -2,10 +2,15 @@
 pub enum Status
 {
 	Okay                = 200,
+	NoContent           = 204,
+	PartialContent      = 206,
+	NotModified         = 304,
 	BadRequest          = 400,
 	NotFound            = 404,
+	MethodNotAllowed    = 405,
 	RangeNotSatisfiable = 416,
 	InternalServerError = 500,
+	ServiceUnavailable  = 503,
 }
 use Status::*;
 
@@ -17,23 +22,15 @@ impl Status
 	{
 		return match self {
 			Okay                => "200 Ok",
+			NoContent           => "204 No Content",
+			PartialContent      => "206 Partial Content",
+			NotModified         => "304 Not Modified",
 			BadRequest          => "400 Bad Request",
 			NotFound            => "404 Not Found",
+			MethodNotAllowed    => "405 Method Not Allowed",
 			RangeNotSatisfiable => "416 Range Not Satisfiable",
 			InternalServerError => "500 Internal Server Error",
-		};
-	}
-
-
-	/// Turn the status code into an entire HTTP response
-	pub fn to_response(&self) -> &'static str
-	{
-		return match self {
-			Okay                => "HTTP/1.1 200 Ok\r\n\r\n",
-			BadRequest          => "HTTP/1.1 400 Bad Request\r\n\r\n",
-			NotFound            => "HTTP/1.1 404 Not Found\r\n\r\n",
-			RangeNotSatisfiable => "HTTP/1.1 416 Range Not Satisfiable\r\n\r\n",
-			InternalServerError => "HTTP/1.1 500 Internal Server Error\r\n\r\n",
+			ServiceUnavailable  => "503 Service Unavailable",
 		};
 	}
 }
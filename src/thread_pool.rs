@@ -1,3 +1,4 @@
+use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::mpsc::Receiver;
@@ -26,13 +27,14 @@ type Job = Box<dyn FnOnce() + Send + 'static>;
 
 impl ThreadPool
 {
-	/// Create with the recommended number of threads, otherwise 1
-	pub fn new() -> ThreadPool
+	/// Create with the number of threads, where 0 is the recommended number of threads, otherwise 1
+	pub fn new(count: usize) -> ThreadPool
 	{
 		// Get the number of threads or 1
-		let count = match std::thread::available_parallelism() {
-			Ok(count) => count.get(),
-			Err(_) => 1,
+		let count = match (count, std::thread::available_parallelism()) {
+			(1.., _) => count,
+			(0, Ok(count)) => count.get(),
+			(0, Err(_)) => 1,
 		};
 
 		// Create sender and receiver for communication
@@ -99,8 +101,8 @@ impl Thread
 				}
 			};
 
-			// Execute the job
-			job();
+			// Execute the job, where a panic ends the job but not the thread
+			let _ = std::panic::catch_unwind(AssertUnwindSafe(job));
 		});
 
 		return Thread { thread: Some(thread) };
@@ -0,0 +1,135 @@
+use std::fs::Metadata;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use crate::http_date::*;
+use crate::request::*;
+use crate::response::*;
+
+
+/// Identifies a version of some content, so clients can avoid downloading it again
+pub struct Validator
+{
+	/// Quoted entity tag like `"1a-2b"`, or a weak one like `W/"1a-2b"`
+	pub etag: String,
+	/// Modification time of a file
+	pub last_modified: Option<SystemTime>,
+}
+
+
+impl Validator
+{
+	/// Create from content in memory with a hash, where weak means it's only equivalent
+	pub fn from_content(content: &[u8], weak: bool) -> Validator
+	{
+		let prefix = match weak {
+			true => "W/",
+			false => "",
+		};
+
+		return Validator {
+			etag: format!("{prefix}\"{:016x}\"", hash(content)),
+			last_modified: None,
+		};
+	}
+
+
+	/// Create from a file's size and modification time
+	pub fn from_metadata(metadata: &Metadata) -> Validator
+	{
+		let last_modified = metadata.modified().ok();
+
+		let nanoseconds = match last_modified.map(|time| time.duration_since(UNIX_EPOCH)) {
+			Some(Ok(duration)) => duration.as_nanos(),
+			_ => 0,
+		};
+
+		return Validator {
+			etag: format!("\"{:x}-{:x}\"", metadata.len(), nanoseconds),
+			last_modified,
+		};
+	}
+
+
+	/// Get a different entity tag for another version made from the same content, like part of a video
+	pub fn with_variant(self, variant: &str) -> Validator
+	{
+		return Validator {
+			etag: format!("{}-{:x}\"", self.etag.trim_end_matches('"'), hash(variant.as_bytes())),
+			last_modified: self.last_modified,
+		};
+	}
+
+
+	/// Add the `ETag` and `Last-Modified` headers to the response
+	pub fn add_headers(&self, response: &mut Response)
+	{
+		response.add_header("ETag", &self.etag);
+		if let Some(last_modified) = self.last_modified {
+			response.add_header("Last-Modified", &format_http_date(last_modified));
+		}
+	}
+
+
+	/// Whether the client already has this version, so the content can be skipped
+	pub fn is_not_modified(&self, request: &Request) -> bool
+	{
+		// Compare the entity tags weakly, ignoring the modification time
+		if !request.if_none_match.is_empty() {
+			return request.if_none_match.iter()
+				.any(|tag| tag == "*" || opaque_tag(tag) == opaque_tag(&self.etag));
+		}
+
+		return match (request.if_modified_since, self.last_modified) {
+			(Some(since), Some(last_modified)) => to_seconds(last_modified) <= to_seconds(since),
+			_ => false,
+		};
+	}
+
+
+	/// Whether the client's partial content is this version, so the ranges can be used
+	pub fn allows_ranges(&self, request: &Request) -> bool
+	{
+		return match &request.if_range {
+			None => true,
+			// Compare the entity tags strongly
+			Some(IfRange::ETag(tag)) => !tag.starts_with("W/") && !self.etag.starts_with("W/") && *tag == self.etag,
+			Some(IfRange::Date(date)) => match self.last_modified {
+				Some(last_modified) => to_seconds(last_modified) == to_seconds(*date),
+				None => false,
+			},
+		};
+	}
+}
+
+
+/// Get the entity tag without the weak prefix
+fn opaque_tag(tag: &str) -> &str
+{
+	return tag.strip_prefix("W/").unwrap_or(tag);
+}
+
+
+/// Get the whole seconds since the Unix epoch, since HTTP dates don't have fractions
+fn to_seconds(time: SystemTime) -> u64
+{
+	return match time.duration_since(UNIX_EPOCH) {
+		Ok(duration) => duration.as_secs(),
+		Err(_) => 0,
+	};
+}
+
+
+/// Hash bytes with 64-bit FNV-1a
+fn hash(bytes: &[u8]) -> u64
+{
+	const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+	const PRIME: u64 = 0x100000001b3;
+
+	let mut result = OFFSET_BASIS;
+	for &byte in bytes {
+		result ^= byte as u64;
+		result = result.wrapping_mul(PRIME);
+	}
+	return result;
+}
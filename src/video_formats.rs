@@ -0,0 +1,26 @@
+/// Video file extensions and their content types, where the first one is used if a video is in multiple formats
+/// https://developer.mozilla.org/en-US/docs/Web/Media/Guides/Formats/Containers
+pub const VIDEO_FORMATS: [(&str, &str); 6] = [
+	(".mp4",  "video/mp4"),
+	(".m4v",  "video/mp4"),
+	(".webm", "video/webm"),
+	(".mkv",  "video/x-matroska"),
+	(".mov",  "video/quicktime"),
+	(".ogv",  "video/ogg"),
+];
+
+
+/// Get the extension and content type of a video extension, which is static
+pub fn find_video_format(extension: &str) -> Option<(&'static str, &'static str)>
+{
+	return VIDEO_FORMATS.iter()
+		.find(|(video_extension, _content_type)| *video_extension == extension)
+		.copied();
+}
+
+
+/// Get the content type of a video extension, for example `".webm"` is `"video/webm"`
+pub fn video_content_type(extension: &str) -> Option<&'static str>
+{
+	return find_video_format(extension).map(|(_extension, content_type)| content_type);
+}
@@ -0,0 +1,68 @@
+/// A subtitle which is shown for a while
+pub struct Cue
+{
+	/// Time in seconds it's shown
+	pub start: f64,
+	/// Time in seconds it's hidden
+	pub end: f64,
+	/// Settings after the times like "line:0 align:start", which is empty if there are none
+	pub settings: String,
+	/// Text with WebVTT tags like `<i>`, which doesn't have blank lines
+	pub text: String,
+}
+
+
+/// Make a WebVTT file from the cues
+pub fn make_webvtt(cues: &[Cue]) -> String
+{
+	let mut result = String::from("WEBVTT\n");
+	for cue in cues {
+		result += &format!("\n{} --> {}", format_timestamp(cue.start), format_timestamp(cue.end));
+		if !cue.settings.is_empty() {
+			result += " ";
+			result += &cue.settings;
+		}
+		result += "\n";
+		result += &cue.text;
+		result += "\n";
+	}
+	return result;
+}
+
+
+/// Add a cue unless it's empty, where it's joined with the last one if it continues it
+pub fn add_cue(cues: &mut Vec<Cue>, cue: Cue)
+{
+	if cue.text.is_empty() || cue.end <= cue.start {
+		return;
+	}
+	if let Some(last) = cues.last_mut() && last.end == cue.start && last.text == cue.text && last.settings == cue.settings {
+		last.end = cue.end;
+		return;
+	}
+	cues.push(cue);
+}
+
+
+/// Get plain text as the text of a cue, where the special characters are escaped and blank lines are removed,
+/// since a blank line ends a cue
+pub fn escape_webvtt(text: &str) -> String
+{
+	return text.replace("\r\n", "\n")
+		.replace('\r', "\n")
+		.split('\n')
+		.map(str::trim_end)
+		.filter(|line| !line.is_empty())
+		.map(|line| line.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;"))
+		.collect::<Vec<String>>()
+		.join("\n");
+}
+
+
+/// Format a time in seconds like `01:02:03.456`
+fn format_timestamp(seconds: f64) -> String
+{
+	let milliseconds = (seconds.max(0.0) * 1000.0).round() as u64;
+	return format!("{:02}:{:02}:{:02}.{:03}",
+		milliseconds / 3_600_000, milliseconds / 60_000 % 60, milliseconds / 1000 % 60, milliseconds % 1000);
+}